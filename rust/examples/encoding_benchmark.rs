@@ -0,0 +1,87 @@
+use diem_scheduler::solver::types::Problem;
+use diem_scheduler::{solve_with_config, Backend, Encoding, SolverConfig};
+use std::env;
+use std::fs;
+use std::time::Instant;
+
+// Runs per encoding and backend; GA results vary between runs, so report the spread.
+const RUNS: usize = 3;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let file_path = if args.len() > 1 {
+        &args[1]
+    } else {
+        "../problem.json"
+    };
+
+    println!("Loading Problem from: {}", file_path);
+    let data = fs::read_to_string(file_path).expect("Failed to read file");
+    let problem: Problem = serde_json::from_str(&data).expect("Failed to parse JSON");
+    println!(
+        "Benchmarking {} slots and {} floating activities, {} runs per setup",
+        problem.total_slots,
+        problem.floating_indices.len(),
+        RUNS
+    );
+
+    let setups = [
+        (Encoding::SlotIndexed, Backend::Genetic),
+        (Encoding::OccurrenceIndexed, Backend::Genetic),
+        (Encoding::SlotIndexed, Backend::Annealing),
+        (Encoding::OccurrenceIndexed, Backend::Annealing),
+    ];
+    for (encoding, backend) in setups {
+        let config = SolverConfig {
+            max_generations: 1200,
+            time_limit_ms: 1000,
+            encoding,
+            backend,
+            ..SolverConfig::default()
+        };
+
+        let mut millis = Vec::with_capacity(RUNS);
+        let mut results = Vec::with_capacity(RUNS);
+        for _ in 0..RUNS {
+            let started = Instant::now();
+            let candidates = solve_with_config(problem.clone(), &config).expect("solve failed");
+            millis.push(started.elapsed().as_millis());
+            if let Some(best) = candidates.into_iter().next() {
+                results.push(best);
+            }
+        }
+
+        println!("\n=== {:?} / {:?} ===", encoding, backend);
+        println!("  time (ms):       {:?}", millis);
+        println!(
+            "  hard violations: {:?}",
+            results
+                .iter()
+                .map(|best| best.breakdown.hard_violations)
+                .collect::<Vec<_>>()
+        );
+        println!(
+            "  user penalty:    {:?}",
+            results
+                .iter()
+                .map(|best| best.breakdown.constraint_penalty.round())
+                .collect::<Vec<_>>()
+        );
+        // The idle reward counts unused genes, which differ per encoding; leave it out so
+        // soft scores compare across encodings.
+        println!(
+            "  soft score:      {:?}",
+            results
+                .iter()
+                .map(|best| (best.breakdown.soft_score() - best.breakdown.idle_reward).round())
+                .collect::<Vec<_>>()
+        );
+        println!(
+            "  occurrences:     {:?}",
+            results
+                .iter()
+                .map(|best| best.placements.len())
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use diem_scheduler::greedy_schedule;
+use diem_scheduler::solver::candidate_slots::build_candidate_start_slots;
+use diem_scheduler::solver::fitness::{DiemFitness, PlacementMove};
+use diem_scheduler::solver::local_search;
+use diem_scheduler::solver::types::{
+    Activity, ActivityType, Binding, FrequencyTarget, GlobalConstraint, Problem, TimeScope,
+    TimeSlot, UserFrequencyConstraint,
+};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const DAY_SLOTS: u16 = 96;
+const FLOATING_ACTIVITIES: usize = 20;
+const EVALUATIONS: u32 = 2000;
+
+fn activity(id: usize, activity_type: ActivityType, duration_slots: u16) -> Activity {
+    Activity {
+        id,
+        activity_type,
+        duration_slots,
+        priority: 1.0 + (id % 3) as f32,
+        assigned_start: None,
+        category_id: id % 4,
+        input_bindings: Vec::<Binding>::new(),
+        output_bindings: Vec::<Binding>::new(),
+        frequency_targets: Vec::<FrequencyTarget>::new(),
+        user_frequency_constraints: Vec::<UserFrequencyConstraint>::new(),
+        occurrence_spacing: vec![],
+        buffer_before: 0,
+        buffer_after: 0,
+        location_id: None,
+        resource_requirements: vec![],
+        attention_free: false,
+        overlap_compatible_categories: vec![],
+        participant_ids: vec![],
+    }
+}
+
+// Daily habits with a learned slot per day, one fixed meeting a day and blocked nights.
+fn build_problem(days: u16) -> Problem {
+    let mut activities = Vec::new();
+    let mut heatmap = Vec::new();
+    for id in 0..FLOATING_ACTIVITIES {
+        let mut habit = activity(id, ActivityType::Floating, 2 + (id % 4) as u16);
+        habit.frequency_targets.push(FrequencyTarget {
+            scope: TimeScope::SameDay,
+            target_count: 1,
+            weight: 2.0,
+        });
+        habit
+            .user_frequency_constraints
+            .push(UserFrequencyConstraint {
+                scope: TimeScope::SameWeek,
+                min_count: Some(3),
+                max_count: None,
+                deadline_end: None,
+                penalty_weight: 500.0,
+            });
+        for day in 0..days {
+            let slot = day * DAY_SLOTS + 32 + (id as u16 * 3) % 40;
+            heatmap.push((id, slot, 0.3));
+        }
+        activities.push(habit);
+    }
+
+    let mut fixed_indices = Vec::new();
+    let mut global_constraints = Vec::new();
+    for day in 0..days {
+        let id = activities.len();
+        let mut meeting = activity(id, ActivityType::Fixed, 4);
+        meeting.assigned_start = Some(day * DAY_SLOTS + 40);
+        activities.push(meeting);
+        fixed_indices.push(id);
+        global_constraints.push(GlobalConstraint::ForbiddenZone {
+            start: day * DAY_SLOTS,
+            end: day * DAY_SLOTS + 28,
+            activity_id: None,
+        });
+    }
+
+    Problem {
+        activities,
+        floating_indices: (0..FLOATING_ACTIVITIES).collect(),
+        fixed_indices,
+        global_constraints,
+        heatmap,
+        markov_matrix: (0..FLOATING_ACTIVITIES)
+            .map(|id| (id, (id + 1) % FLOATING_ACTIVITIES, 0.2))
+            .collect(),
+        total_slots: days * DAY_SLOTS - 4,
+        day_start_offset: 0,
+        category_switch_costs: vec![],
+        category_batching_bonus: vec![],
+        travel_times: vec![],
+        resources: vec![],
+        participants: vec![],
+    }
+}
+
+fn main() {
+    println!(
+        "Fitness evaluation of the greedy schedule, {} evaluations per horizon",
+        EVALUATIONS
+    );
+    for days in [1, 7, 28] {
+        let problem = build_problem(days);
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let fitness = DiemFitness::new(problem.clone(), candidate_slots);
+        let genes = fitness.encode(&greedy_schedule(&problem));
+
+        let started = Instant::now();
+        for _ in 0..EVALUATIONS {
+            black_box(fitness.evaluate(black_box(&genes)));
+        }
+        let per_evaluation = started.elapsed() / EVALUATIONS;
+        println!(
+            "  {:>2} days, {:>4} genes: {:>8.1?} per evaluation",
+            days,
+            genes.len(),
+            per_evaluation
+        );
+    }
+
+    println!("\nOne shifted occurrence: move evaluation versus scoring the placements afresh");
+    for days in [1, 7, 28] {
+        let problem = build_problem(days);
+        let fitness = DiemFitness::new(problem.clone(), build_candidate_start_slots(&problem));
+        // Activity ids equal their indices in this problem.
+        let placements: Vec<(usize, TimeSlot)> = greedy_schedule(&problem);
+        let idle = (fitness.candidate_start_slots.len() - placements.len()) as u32;
+        let mut schedule = fitness.incremental_schedule(&placements, idle);
+        let shift = PlacementMove::Shift(0, placements[0].1 + 1);
+        let mut shifted = placements.clone();
+        shifted[0].1 += 1;
+
+        let started = Instant::now();
+        for _ in 0..EVALUATIONS {
+            black_box(fitness.evaluate_move(&mut schedule, black_box(shift)));
+        }
+        let per_move = started.elapsed() / EVALUATIONS;
+        let started = Instant::now();
+        for _ in 0..EVALUATIONS {
+            black_box(fitness.evaluate_placements(black_box(&shifted), idle));
+        }
+        let per_rescore = started.elapsed() / EVALUATIONS;
+        println!(
+            "  {:>2} days, {:>3} placements: {:>8.1?} per move, {:>8.1?} rescored",
+            days,
+            placements.len(),
+            per_move,
+            per_rescore
+        );
+    }
+
+    println!("\nLocal search from the greedy schedule to its local optimum");
+    for days in [3, 7] {
+        let problem = build_problem(days);
+        let fitness = DiemFitness::new(problem.clone(), build_candidate_start_slots(&problem));
+        let genes = fitness.candidate_start_slots.len();
+        let start = greedy_schedule(&problem);
+        let started = Instant::now();
+        let improved = local_search::improve(&fitness, &start, genes, Duration::from_secs(60));
+        println!(
+            "  {} days: {:>8.1?}, soft score {:.1} -> {:.1}",
+            days,
+            started.elapsed(),
+            local_search::evaluate(&fitness, &start, genes).soft_score(),
+            local_search::evaluate(&fitness, &improved, genes).soft_score()
+        );
+    }
+}
@@ -66,6 +66,14 @@ fn activity(
         output_bindings: Vec::<Binding>::new(),
         frequency_targets: Vec::<FrequencyTarget>::new(),
         user_frequency_constraints: Vec::<UserFrequencyConstraint>::new(),
+        occurrence_spacing: vec![],
+        buffer_before: 0,
+        buffer_after: 0,
+        location_id: None,
+        resource_requirements: vec![],
+        attention_free: false,
+        overlap_compatible_categories: vec![],
+        participant_ids: vec![],
     }
 }
 
@@ -178,6 +186,12 @@ fn build_scenario(name: &'static str, with_hard_frequency: bool) -> DemoScenario
             // Soft sequence preference: Deep Work followed by Workout near-adjacent.
             markov_matrix: vec![(DEEP_WORK_ACTIVITY_ID, WORKOUT_ACTIVITY_ID, 1.4)],
             total_slots: TOTAL_SLOTS,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         },
         labels_by_index: vec![
             "Deep Work: API Design",
@@ -366,6 +380,69 @@ fn print_plain_english_rules(scenario: &DemoScenario) {
                         .unwrap_or_default()
                 );
             }
+            GlobalConstraint::Precedence {
+                predecessor_id,
+                successor_id,
+                min_lag,
+                max_lag,
+                ..
+            } => {
+                println!(
+                    "    - Precedence: '{}' must start {}..{} slots after '{}' ends.",
+                    activity_name_for_id(scenario, *successor_id),
+                    min_lag,
+                    max_lag.map_or("any".to_string(), |lag| lag.to_string()),
+                    activity_name_for_id(scenario, *predecessor_id)
+                );
+            }
+            GlobalConstraint::WorkloadLimit {
+                scope,
+                category_id,
+                max_busy_slots,
+                hard,
+                ..
+            } => {
+                println!(
+                    "    - Workload: category {:?} may be busy at most {} slots {} ({}).",
+                    category_id,
+                    max_busy_slots,
+                    scope_to_english(scope),
+                    if *hard { "hard" } else { "soft" }
+                );
+            }
+            GlobalConstraint::FreeTimeTarget {
+                scope,
+                min_free_slots,
+                ..
+            } => {
+                println!(
+                    "    - Free time: keep at least {} slots unscheduled {}.",
+                    min_free_slots,
+                    scope_to_english(scope)
+                );
+            }
+            GlobalConstraint::ContinuousBusyLimit {
+                category_id,
+                max_busy_slots,
+                min_break_slots,
+                ..
+            } => {
+                println!(
+                    "    - Breaks: category {:?} needs a {}-slot break after {} busy slots.",
+                    category_id, min_break_slots, max_busy_slots
+                );
+            }
+            GlobalConstraint::Exclusion {
+                first,
+                second,
+                window,
+                ..
+            } => {
+                println!(
+                    "    - Exclusion: {:?} and {:?} must not co-occur within {:?}.",
+                    first, second, window
+                );
+            }
         }
     }
 
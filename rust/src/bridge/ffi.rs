@@ -1,5 +1,7 @@
-use super::serialization::{deserialize_problem, serialize_result};
+use super::serialization::{deserialize_config, deserialize_problem, serialize_result};
 use crate::solver;
+use crate::solver::types::Problem;
+use std::error::Error;
 use std::slice;
 
 #[repr(C)]
@@ -8,19 +10,92 @@ pub struct DiemResult {
     pub len: usize,
 }
 
+impl DiemResult {
+    fn empty() -> Self {
+        DiemResult {
+            ptr: std::ptr::null_mut(),
+            len: 0,
+        }
+    }
+}
+
 /// Main entry point for the JSI/Nitro bridge.
+///
+/// # Safety
+///
+/// `data_ptr` must point to `len` readable bytes of MessagePack-encoded `Problem`.
+/// The returned buffer must be released with `diem_result_free`.
 #[no_mangle]
 pub unsafe extern "C" fn diem_solve(
     data_ptr: *const u8,
     len: usize,
     max_generations: usize,
     time_limit_ms: u64,
+) -> DiemResult {
+    run_with_problem("diem_solve", data_ptr, len, |problem| {
+        log::info!("Calling solver::solve");
+        solver::solve(problem, max_generations, time_limit_ms)
+    })
+}
+
+/// `diem_solve` with a full MessagePack-encoded `SolverConfig` (objective, encoding,
+/// backend, alternatives, ...). Returns a MessagePack list of `ScheduleCandidate`, best first.
+///
+/// # Safety
+///
+/// `data_ptr` must point to `len` readable bytes of MessagePack-encoded `Problem`, and
+/// `config_ptr` to `config_len` readable bytes of MessagePack-encoded `SolverConfig`.
+/// The returned buffer must be released with `diem_result_free`.
+#[no_mangle]
+pub unsafe extern "C" fn diem_solve_with_config(
+    data_ptr: *const u8,
+    len: usize,
+    config_ptr: *const u8,
+    config_len: usize,
+) -> DiemResult {
+    if config_ptr.is_null() || config_len == 0 {
+        return DiemResult::empty();
+    }
+    let config_bytes = slice::from_raw_parts(config_ptr, config_len);
+    run_with_problem("diem_solve_with_config", data_ptr, len, move |problem| {
+        log::info!("Deserializing solver config");
+        let config = deserialize_config(config_bytes)?;
+        log::info!("Calling solver::solve_with_config");
+        solver::solve_with_config(problem, &config)
+    })
+}
+
+/// "Find me time for X": the best `limit` start slots for one more occurrence of
+/// `activity_id`, serialized as a MessagePack list of `SlotSuggestion`.
+///
+/// # Safety
+///
+/// `data_ptr` must point to `len` readable bytes of MessagePack-encoded `Problem`.
+/// The returned buffer must be released with `diem_result_free`.
+#[no_mangle]
+pub unsafe extern "C" fn diem_find_slots(
+    data_ptr: *const u8,
+    len: usize,
+    activity_id: usize,
+    duration_slots: u16,
+    limit: usize,
+) -> DiemResult {
+    run_with_problem("diem_find_slots", data_ptr, len, |problem| {
+        log::info!("Calling solver::slot_finder::find_slots");
+        solver::slot_finder::find_slots(&problem, activity_id, duration_slots, limit)
+    })
+}
+
+// Deserializes the problem, runs `solve` on it and serializes the result, logging and
+// returning an empty result on any failure or panic.
+unsafe fn run_with_problem<T: serde::Serialize>(
+    entry_point: &str,
+    data_ptr: *const u8,
+    len: usize,
+    solve: impl FnOnce(Problem) -> Result<T, Box<dyn Error>> + std::panic::UnwindSafe,
 ) -> DiemResult {
     if data_ptr.is_null() || len == 0 {
-        return DiemResult {
-            ptr: std::ptr::null_mut(),
-            len: 0,
-        };
+        return DiemResult::empty();
     }
 
     let result = std::panic::catch_unwind(|| {
@@ -32,15 +107,11 @@ pub unsafe extern "C" fn diem_solve(
             Ok(p) => p,
             Err(e) => {
                 log::error!("Deserialization failed: {:?}", e);
-                return DiemResult {
-                    ptr: std::ptr::null_mut(),
-                    len: 0,
-                };
+                return DiemResult::empty();
             }
         };
 
-        log::info!("Calling solver::solve");
-        match solver::solve(problem, max_generations, time_limit_ms) {
+        match solve(problem) {
             Ok(results) => {
                 log::info!("Solver complete. Serializing result.");
                 match serialize_result(&results) {
@@ -54,34 +125,25 @@ pub unsafe extern "C" fn diem_solve(
                     }
                     Err(e) => {
                         log::error!("Serialization failed: {:?}", e);
-                        DiemResult {
-                            ptr: std::ptr::null_mut(),
-                            len: 0,
-                        }
+                        DiemResult::empty()
                     }
                 }
             }
             Err(e) => {
                 log::error!("Solver failed: {:?}", e);
-                DiemResult {
-                    ptr: std::ptr::null_mut(),
-                    len: 0,
-                }
+                DiemResult::empty()
             }
         }
     });
 
     match result {
         Ok(res) => {
-            log::info!("Successfully returning out of diem_solve");
+            log::info!("Successfully returning out of {}", entry_point);
             res
         }
         Err(e) => {
-            log::error!("Rust panicked in diem_solve: {:?}", e);
-            DiemResult {
-                ptr: std::ptr::null_mut(),
-                len: 0,
-            }
+            log::error!("Rust panicked in {}: {:?}", entry_point, e);
+            DiemResult::empty()
         }
     }
 }
@@ -9,6 +9,9 @@ pub extern "C" fn diem_alloc(size: usize) -> *mut u8 {
     ptr
 }
 
+/// # Safety
+///
+/// `ptr` must come from `diem_alloc` called with the same `size`.
 #[no_mangle]
 pub unsafe extern "C" fn diem_free(ptr: *mut u8, size: usize) {
     if ptr.is_null() {
@@ -17,6 +20,10 @@ pub unsafe extern "C" fn diem_free(ptr: *mut u8, size: usize) {
     let _ = Vec::from_raw_parts(ptr, 0, size);
 }
 
+/// # Safety
+///
+/// `ptr` and `len` must describe a buffer returned by `diem_solve`, `diem_solve_with_config`
+/// or `diem_find_slots`.
 #[no_mangle]
 pub unsafe extern "C" fn diem_result_free(ptr: *mut u8, len: usize) {
     if ptr.is_null() {
@@ -1,4 +1,5 @@
 use crate::solver::types::Problem;
+use crate::solver::SolverConfig;
 use std::error::Error;
 
 pub fn deserialize_problem(data: &[u8]) -> Result<Problem, Box<dyn Error>> {
@@ -6,6 +7,11 @@ pub fn deserialize_problem(data: &[u8]) -> Result<Problem, Box<dyn Error>> {
     Ok(problem)
 }
 
+pub fn deserialize_config(data: &[u8]) -> Result<SolverConfig, Box<dyn Error>> {
+    let config: SolverConfig = rmp_serde::from_slice(data)?;
+    Ok(config)
+}
+
 pub fn serialize_result<T: serde::Serialize>(data: &T) -> Result<Vec<u8>, Box<dyn Error>> {
     let mut buf = Vec::new();
     let mut serializer = rmp_serde::Serializer::new(&mut buf);
@@ -1,5 +1,11 @@
 pub mod bridge;
 pub mod solver;
 
-pub use solver::solve;
+pub use solver::backend::GaParameters;
+pub use solver::candidate_slots::{free_intervals, FreeInterval, FreeIntervalFilter};
+pub use solver::greedy::greedy_schedule;
+pub use solver::slot_finder::{find_slots, SlotSuggestion};
 pub use solver::types::{Activity, GlobalConstraint, Problem};
+pub use solver::{
+    solve, solve_pareto, solve_with_config, Backend, Encoding, ScheduleCandidate, SolverConfig,
+};
@@ -0,0 +1,146 @@
+use super::backend::{SeededFitness, SolverBackend};
+use super::SolverConfig;
+use genetic_algorithm::chromosome::Chromosome;
+use genetic_algorithm::genotype::{Genotype, RangeGenotype};
+use rand::RngExt;
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+// Moves per configured generation, matching the GA's evaluations per generation.
+const ANNEALING_MOVES_PER_GENERATION: usize = 600;
+// Random moves sampled from the seed to pick the starting temperature.
+const ANNEALING_CALIBRATION_MOVES: usize = 64;
+// Temperature at the end of the run, relative to the starting temperature.
+const ANNEALING_FINAL_TEMPERATURE_RATIO: f64 = 1e-4;
+// Moves without a new best before the run stops early.
+const ANNEALING_MAX_STALE_MOVES: usize = 20 * ANNEALING_MOVES_PER_GENERATION;
+// Best-so-far snapshots kept as alternatives.
+const ANNEALING_MAX_SNAPSHOTS: usize = 64;
+
+/// Simulated annealing from the greedy seed with single-gene and swap moves under
+/// geometric cooling. Runs `max_generations * 600` moves, until `time_limit_ms`, or until
+/// the best schedule has not improved for 20 generations' worth of moves.
+///
+/// The starting temperature is the lower quartile of worsening deltas around the seed,
+/// so soft-score losses are accepted early while hard-tier losses almost never are.
+/// Alternatives are the earlier best-so-far schedules, most recent first.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SimulatedAnnealing;
+
+impl SolverBackend for SimulatedAnnealing {
+    fn search<F: SeededFitness>(
+        &self,
+        genes_size: usize,
+        max_allele: u16,
+        mut fitness: F,
+        config: &SolverConfig,
+    ) -> Result<Vec<Vec<u16>>, Box<dyn Error>> {
+        let genotype = RangeGenotype::builder()
+            .with_genes_size(genes_size)
+            .with_allele_range(0..=max_allele)
+            .build()
+            .map_err(|e| format!("Genotype build error: {:?}", e))?;
+        if genes_size == 0 {
+            return Ok(vec![]);
+        }
+        // Start from the greedy seed when it fits the genotype, else an empty schedule.
+        let mut current = fitness.seed_genes();
+        if current.len() != genes_size {
+            current = vec![max_allele; genes_size];
+        }
+        let mut score = |genes: &[u16]| {
+            fitness
+                .calculate_for_chromosome(&Chromosome::new(genes.to_vec()), &genotype)
+                .unwrap_or(isize::MIN)
+        };
+        let mut rng = rand::rng();
+
+        let mut current_score = score(&current);
+
+        let mut worsening: Vec<isize> = (0..ANNEALING_CALIBRATION_MOVES)
+            .filter_map(|_| {
+                let mut neighbour = current.clone();
+                perturb(&mut neighbour, max_allele, &mut rng);
+                let loss = current_score.saturating_sub(score(&neighbour));
+                (loss > 0).then_some(loss)
+            })
+            .collect();
+        worsening.sort_unstable();
+        let initial_temperature = worsening
+            .get(worsening.len() / 4)
+            .map_or(1.0, |&loss| loss as f64);
+
+        let max_moves = config
+            .max_generations
+            .saturating_mul(ANNEALING_MOVES_PER_GENERATION)
+            .max(1);
+        let time_limit = Duration::from_millis(config.time_limit_ms);
+        let started = Instant::now();
+
+        let mut best = current.clone();
+        let mut best_score = current_score;
+        let mut snapshots = Vec::<Vec<u16>>::new();
+        let mut last_improvement = 0;
+        for step in 0..max_moves {
+            if step - last_improvement > ANNEALING_MAX_STALE_MOVES {
+                break;
+            }
+            let mut progress = step as f64 / max_moves as f64;
+            if config.time_limit_ms > 0 {
+                let elapsed = started.elapsed();
+                if elapsed >= time_limit {
+                    break;
+                }
+                progress = progress.max(elapsed.as_secs_f64() / time_limit.as_secs_f64());
+            }
+            let temperature =
+                initial_temperature * ANNEALING_FINAL_TEMPERATURE_RATIO.powf(progress);
+
+            let mut neighbour = current.clone();
+            perturb(&mut neighbour, max_allele, &mut rng);
+            let neighbour_score = score(&neighbour);
+            let delta = neighbour_score.saturating_sub(current_score);
+            let accept =
+                delta >= 0 || rng.random_bool((delta as f64 / temperature).exp().clamp(0.0, 1.0));
+            if !accept {
+                continue;
+            }
+            current = neighbour;
+            current_score = neighbour_score;
+            if current_score > best_score {
+                if config.alternatives > 1 {
+                    snapshots.push(std::mem::replace(&mut best, current.clone()));
+                    if snapshots.len() > ANNEALING_MAX_SNAPSHOTS {
+                        snapshots.remove(0);
+                    }
+                } else {
+                    best.clone_from(&current);
+                }
+                best_score = current_score;
+                last_improvement = step;
+            }
+        }
+        log::info!(
+            "-> Annealing solver finished. Best fitness score: {:?}",
+            best_score
+        );
+
+        let mut pool = vec![best];
+        if config.alternatives > 1 {
+            pool.push(current);
+            pool.extend(snapshots.into_iter().rev());
+        }
+        Ok(pool)
+    }
+}
+
+// Reassigns one gene to a random allele, or swaps the alleles of two genes.
+fn perturb(genes: &mut [u16], max_allele: u16, rng: &mut impl RngExt) {
+    let i = rng.random_range(0..genes.len());
+    if rng.random_bool(0.5) {
+        genes[i] = rng.random_range(0..=max_allele);
+    } else {
+        let j = rng.random_range(0..genes.len());
+        genes.swap(i, j);
+    }
+}
@@ -0,0 +1,659 @@
+use super::annealing::SimulatedAnnealing;
+use super::operators::{CrossoverDayBlock, MutateSchedule, ScheduleLayout};
+use super::{Backend, GeneticOperators, SolverConfig};
+use genetic_algorithm::chromosome::Chromosome;
+use genetic_algorithm::fitness::{Fitness, FitnessOrdering, FitnessValue};
+use genetic_algorithm::strategy::evolve::prelude::*;
+use genetic_algorithm::strategy::{
+    StrategyAction, StrategyConfig, StrategyReporter, StrategyState,
+};
+use rand_08::Rng;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::error::Error;
+use std::thread;
+use std::time::{Duration, Instant};
+use thread_local::ThreadLocal;
+
+// Bounds of the GA tuning derived from problem size.
+const GA_MIN_POPULATION_SIZE: usize = 50;
+const GA_MAX_POPULATION_SIZE: usize = 1000;
+const GA_MIN_STALE_GENERATIONS: usize = 40;
+const GA_MAX_STALE_GENERATIONS: usize = 300;
+const GA_MAX_MUTATION_COUNT: usize = 10;
+// Genes per extra gene mutation, so long chromosomes still move several occurrences.
+const GA_GENES_PER_MUTATION: usize = 300;
+// Conservative on-device cost of one evaluation per gene, and the generations a time
+// budget should leave room for.
+const GA_EVALUATION_NANOS_PER_GENE: u64 = 50;
+const GA_BUDGET_GENERATIONS: u64 = 100;
+
+// Earlier best chromosomes kept per run as extra alternatives, most recent first.
+const IMPROVEMENT_TRAIL_SIZE: usize = 32;
+
+// Island model: generations between migrations and the smallest island population.
+const MIGRATION_INTERVAL: usize = 25;
+const ISLAND_MIN_POPULATION_SIZE: usize = 60;
+
+/// GA tuning for one solve. Derived from the problem by `GaParameters::scaled` unless
+/// `SolverConfig::ga_parameters` sets it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GaParameters {
+    pub population_size: usize,
+    /// Generations without improvement before the GA stops.
+    pub max_stale_generations: usize,
+    /// Upper bound of genes changed in one mutated chromosome.
+    pub mutation_count: usize,
+    /// Chance that an offspring is mutated at all.
+    pub mutation_probability: f32,
+}
+
+impl GaParameters {
+    /// Population and staleness grow with the genes and floating activities to search,
+    /// more genes allow more mutations per chromosome, and more activities raise the
+    /// mutation probability. A `time_limit_ms` budget caps the population so that about
+    /// `GA_BUDGET_GENERATIONS` generations fit in it.
+    pub fn scaled(genes_size: usize, floating_count: usize, time_limit_ms: u64) -> Self {
+        let mut population_size = (genes_size / 2 + 20 * floating_count)
+            .clamp(GA_MIN_POPULATION_SIZE, GA_MAX_POPULATION_SIZE);
+        if time_limit_ms > 0 {
+            let generation_nanos =
+                GA_BUDGET_GENERATIONS * GA_EVALUATION_NANOS_PER_GENE * genes_size.max(1) as u64;
+            let affordable = time_limit_ms.saturating_mul(1_000_000) / generation_nanos;
+            population_size =
+                population_size.min((affordable as usize).max(GA_MIN_POPULATION_SIZE));
+        }
+        Self {
+            population_size,
+            max_stale_generations: (genes_size / 10 + 10 * floating_count)
+                .clamp(GA_MIN_STALE_GENERATIONS, GA_MAX_STALE_GENERATIONS),
+            mutation_count: genes_size
+                .div_ceil(GA_GENES_PER_MUTATION)
+                .clamp(2, GA_MAX_MUTATION_COUNT),
+            mutation_probability: (0.3 + 0.02 * floating_count as f32).min(0.8),
+        }
+    }
+
+    /// Rejects tuning the GA cannot run with, such as a caller-supplied override. With
+    /// `islands > 1` the population must give every island `ISLAND_MIN_POPULATION_SIZE`.
+    pub fn validate(&self, islands: usize) -> Result<(), String> {
+        if self.population_size == 0 {
+            return Err("GA population_size must be at least 1".to_string());
+        }
+        let island_population = islands.saturating_mul(ISLAND_MIN_POPULATION_SIZE);
+        if islands > 1 && self.population_size < island_population {
+            return Err(format!(
+                "GA population_size {} is too small for {} islands of at least {}",
+                self.population_size, islands, ISLAND_MIN_POPULATION_SIZE
+            ));
+        }
+        if self.mutation_count == 0 {
+            return Err("GA mutation_count must be at least 1".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.mutation_probability) {
+            return Err(format!(
+                "GA mutation_probability must be within 0..=1, got {}",
+                self.mutation_probability
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Fitness over `u16` range genes that can also provide a starting chromosome.
+pub trait SeededFitness: Fitness<Genotype = RangeGenotype<u16>> {
+    /// Genes encoding the greedy schedule, used to seed the search.
+    fn seed_genes(&self) -> Vec<u16>;
+
+    /// Floating activities the genes schedule; one input of `GaParameters::scaled`.
+    fn floating_count(&self) -> usize;
+
+    /// Gene layout for the schedule-aware GA operators; `None` keeps the generic ones.
+    fn schedule_layout(&self) -> Option<ScheduleLayout> {
+        None
+    }
+}
+
+/// Search strategy over genes with alleles `0..=max_allele`. Returns the best genes
+/// first, followed by other genes worth offering as alternatives.
+pub trait SolverBackend {
+    fn search<F: SeededFitness>(
+        &self,
+        genes_size: usize,
+        max_allele: u16,
+        fitness: F,
+        config: &SolverConfig,
+    ) -> Result<Vec<Vec<u16>>, Box<dyn Error>>;
+}
+
+/// Runs the search with the backend selected in `config`.
+pub(crate) fn search<F: SeededFitness>(
+    genes_size: usize,
+    max_allele: u16,
+    fitness: F,
+    config: &SolverConfig,
+) -> Result<Vec<Vec<u16>>, Box<dyn Error>> {
+    match config.backend {
+        Backend::Genetic => GeneticBackend.search(genes_size, max_allele, fitness, config),
+        Backend::Annealing => SimulatedAnnealing.search(genes_size, max_allele, fitness, config),
+    }
+}
+
+/// The `genetic_algorithm` Evolve strategy. Alternatives come from the chromosomes that
+/// were the best at some point of the run and from the final population; both may have
+/// converged, so fewer distinct schedules than requested can come back. Stops at
+/// `config.time_limit_ms` when set. With `config.islands > 1` it runs the island model
+/// instead (see `evolve_islands`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GeneticBackend;
+
+impl SolverBackend for GeneticBackend {
+    fn search<F: SeededFitness>(
+        &self,
+        genes_size: usize,
+        max_allele: u16,
+        fitness: F,
+        config: &SolverConfig,
+    ) -> Result<Vec<Vec<u16>>, Box<dyn Error>> {
+        let genotype = RangeGenotype::builder()
+            .with_genes_size(genes_size)
+            .with_allele_range(0..=max_allele)
+            .build()
+            .map_err(|e| format!("Genotype build error: {:?}", e))?;
+        let parameters = config.ga_parameters.unwrap_or_else(|| {
+            GaParameters::scaled(genes_size, fitness.floating_count(), config.time_limit_ms)
+        });
+        if config.islands > 1 {
+            return evolve_islands(genotype, fitness, config, &parameters);
+        }
+
+        let layout = schedule_layout(&fitness, config);
+        let run = EvolveRun {
+            parameters,
+            max_generations: config.max_generations,
+            stop_when_stale: true,
+            keep_population: config.alternatives > 1,
+            deadline: deadline(config),
+        };
+        let result = run_evolve(genotype, fitness, layout.as_ref(), &run)?;
+        let Some((best_score, best_genes)) = result.best else {
+            return Ok(vec![]);
+        };
+        log::info!(
+            "-> GA solver finished. Best fitness score: {:?}",
+            best_score
+        );
+
+        let mut pool = vec![best_genes];
+        pool.extend(result.improvements);
+        pool.extend(result.final_population.into_iter().map(|(genes, _)| genes));
+        Ok(pool)
+    }
+}
+
+// When the search must stop, if `config.time_limit_ms` sets a budget.
+fn deadline(config: &SolverConfig) -> Option<Instant> {
+    (config.time_limit_ms > 0).then(|| Instant::now() + Duration::from_millis(config.time_limit_ms))
+}
+
+// Layout for the schedule-aware operators, when configured and the encoding has one.
+pub(crate) fn schedule_layout<F: SeededFitness>(
+    fitness: &F,
+    config: &SolverConfig,
+) -> Option<ScheduleLayout> {
+    match config.operators {
+        GeneticOperators::ScheduleAware => fitness.schedule_layout(),
+        GeneticOperators::Generic => None,
+    }
+}
+
+/// Island model: `config.islands` populations split `parameters.population_size` evenly
+/// and evolve on their own threads in epochs of `MIGRATION_INTERVAL` generations. There
+/// is at most one island per available core, and a derived population smaller than
+/// `ISLAND_MIN_POPULATION_SIZE` per island runs on fewer islands; overrides were checked
+/// by `GaParameters::validate`. After each epoch every island's best chromosome migrates
+/// to the next island in a ring (see `migrate`), and each island resumes from its own
+/// scored final population with the migrant. Stops at `max_generations` or
+/// once no island has improved the overall best for `parameters.max_stale_generations`,
+/// or at `config.time_limit_ms`, which also cuts the running epoch short.
+fn evolve_islands<F: SeededFitness>(
+    genotype: RangeGenotype<u16>,
+    fitness: F,
+    config: &SolverConfig,
+    parameters: &GaParameters,
+) -> Result<Vec<Vec<u16>>, Box<dyn Error>> {
+    // More islands than cores would only time-slice the same threads.
+    let cores = thread::available_parallelism().map_or(1, |cores| cores.get());
+    let islands = config
+        .islands
+        .min(cores)
+        .min(parameters.population_size / ISLAND_MIN_POPULATION_SIZE)
+        .max(1);
+    let population_size = parameters.population_size / islands;
+    let layout = schedule_layout(&fitness, config);
+    let mut populations: Vec<Vec<ScoredGenes>> = vec![vec![(fitness.seed_genes(), None)]; islands];
+    let mut best: Option<(FitnessValue, Vec<u16>)> = None;
+    let mut improvements = Vec::<Vec<u16>>::new();
+    let mut generations = 0;
+    let mut stale_generations = 0;
+    let deadline = deadline(config);
+
+    while generations < config.max_generations
+        && stale_generations < parameters.max_stale_generations
+        && deadline.is_none_or(|deadline| Instant::now() < deadline)
+    {
+        let run = EvolveRun {
+            parameters: GaParameters {
+                population_size,
+                ..*parameters
+            },
+            max_generations: MIGRATION_INTERVAL.min(config.max_generations - generations),
+            stop_when_stale: false,
+            keep_population: true,
+            deadline,
+        };
+        let results: Vec<Result<EvolveResult, String>> = thread::scope(|scope| {
+            let handles: Vec<_> = populations
+                .iter()
+                .map(|population| {
+                    let genotype = genotype.clone();
+                    let fitness = IslandFitness {
+                        inner: fitness.clone(),
+                        start_population: population.clone(),
+                    };
+                    let layout = layout.as_ref();
+                    let run = &run;
+                    scope.spawn(move || run_evolve(genotype, fitness, layout, run))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| Err("Island thread panicked".to_string()))
+                })
+                .collect()
+        });
+
+        let mut elites = Vec::with_capacity(islands);
+        let mut improved = false;
+        for (population, result) in populations.iter_mut().zip(results) {
+            let result = result?;
+            if let Some((score, genes)) = &result.best {
+                if best
+                    .as_ref()
+                    .is_none_or(|(best_score, _)| score > best_score)
+                {
+                    best = Some((*score, genes.clone()));
+                    improved = true;
+                }
+            }
+            *population = result.final_population;
+            improvements.extend(result.improvements);
+            elites.push(result.best.map(|(score, genes)| (genes, Some(score))));
+        }
+        migrate(&mut populations, elites, population_size);
+
+        generations += run.max_generations;
+        stale_generations = if improved {
+            0
+        } else {
+            stale_generations + run.max_generations
+        };
+    }
+
+    let Some((best_score, best_genes)) = best else {
+        return Ok(vec![]);
+    };
+    log::info!(
+        "-> GA solver finished on {} islands. Best fitness score: {:?}",
+        islands,
+        best_score
+    );
+
+    let mut pool = vec![best_genes];
+    if config.alternatives > 1 {
+        pool.extend(improvements);
+        pool.extend(populations.into_iter().flatten().map(|(genes, _)| genes));
+    }
+    Ok(pool)
+}
+
+/// Ring migration: each island's elite joins the next island's population, which keeps
+/// its `population_size` best chromosomes (unscored ones last, the migrant ahead of ties).
+fn migrate(
+    populations: &mut [Vec<ScoredGenes>],
+    elites: Vec<Option<ScoredGenes>>,
+    population_size: usize,
+) {
+    let islands = populations.len();
+    for (island, elite) in elites.into_iter().enumerate() {
+        if let Some(elite) = elite {
+            let next = &mut populations[(island + 1) % islands];
+            next.insert(0, elite);
+            next.sort_by_key(|(_, score)| Reverse(*score));
+            next.truncate(population_size);
+        }
+    }
+}
+
+// Genes with the fitness score they were evaluated at, if any.
+type ScoredGenes = (Vec<u16>, Option<FitnessValue>);
+
+struct EvolveResult {
+    /// Best `(score, genes)` of the run.
+    best: Option<(FitnessValue, Vec<u16>)>,
+    /// Earlier bests, most recent first, when the run keeps its population.
+    improvements: Vec<Vec<u16>>,
+    /// Last generation with its scores, when the run keeps its population.
+    final_population: Vec<ScoredGenes>,
+}
+
+struct EvolveRun {
+    parameters: GaParameters,
+    max_generations: usize,
+    /// Stop after `parameters.max_stale_generations` without improvement.
+    stop_when_stale: bool,
+    /// Return earlier bests and the final population along with the best chromosome.
+    keep_population: bool,
+    /// Stop after the generation running at this instant.
+    deadline: Option<Instant>,
+}
+
+// Runs Evolve with the schedule operators when a layout is given, else the generic ones.
+fn run_evolve<F: Fitness<Genotype = RangeGenotype<u16>>>(
+    genotype: RangeGenotype<u16>,
+    fitness: F,
+    layout: Option<&ScheduleLayout>,
+    run: &EvolveRun,
+) -> Result<EvolveResult, String> {
+    match layout {
+        Some(layout) => evolve_with(
+            genotype,
+            fitness,
+            MutateSchedule::new(
+                layout.clone(),
+                run.parameters.mutation_count,
+                run.parameters.mutation_probability,
+            ),
+            CrossoverDayBlock::new(layout.clone(), 0.5, 0.5),
+            run,
+        ),
+        None => evolve_with(
+            genotype,
+            fitness,
+            MutateMultiGene::new(
+                run.parameters.mutation_count,
+                run.parameters.mutation_probability,
+            ),
+            CrossoverUniform::new(0.5, 0.5),
+            run,
+        ),
+    }
+}
+
+fn evolve_with<F, M, C>(
+    genotype: RangeGenotype<u16>,
+    fitness: F,
+    mutate: M,
+    crossover: C,
+    run: &EvolveRun,
+) -> Result<EvolveResult, String>
+where
+    F: Fitness<Genotype = RangeGenotype<u16>>,
+    M: Mutate<Genotype = RangeGenotype<u16>>,
+    C: Crossover<Genotype = RangeGenotype<u16>>,
+{
+    let evolve = Evolve::builder()
+        .with_genotype(genotype)
+        .with_fitness(fitness)
+        .with_fitness_ordering(FitnessOrdering::Maximize)
+        .with_target_population_size(run.parameters.population_size)
+        // Always set, so `StopAtDeadline` can end the run through it.
+        .with_max_stale_generations(if run.stop_when_stale {
+            run.parameters.max_stale_generations
+        } else {
+            usize::MAX
+        })
+        .with_max_generations(run.max_generations)
+        .with_select(SelectTournament::new(0.8, 0.1, 4))
+        .with_crossover(crossover)
+        .with_mutate(mutate)
+        .with_extension(StopAtDeadline {
+            deadline: run.deadline,
+        })
+        .with_reporter(PoolReporter {
+            enabled: run.keep_population,
+            improvements: vec![],
+            final_population: vec![],
+        })
+        .call()
+        .map_err(|e| format!("Evolve strategy failed: {:?}", e))?;
+
+    let best = evolve
+        .best_chromosome()
+        .and_then(|chromosome| Some((chromosome.fitness_score()?, chromosome.genes)));
+    let PoolReporter {
+        improvements,
+        final_population,
+        ..
+    } = evolve.reporter;
+    Ok(EvolveResult {
+        best,
+        improvements: improvements.into_iter().rev().collect(),
+        final_population,
+    })
+}
+
+/// Ends the run after the generation that passes `deadline` by counting it as stale for
+/// the configured `max_stale_generations`.
+#[derive(Clone, Debug)]
+struct StopAtDeadline {
+    deadline: Option<Instant>,
+}
+
+impl Extension for StopAtDeadline {
+    type Genotype = RangeGenotype<u16>;
+
+    fn after_generation_complete<R: Rng, SR: StrategyReporter<Genotype = Self::Genotype>>(
+        &mut self,
+        _genotype: &mut Self::Genotype,
+        state: &mut EvolveState<Self::Genotype>,
+        config: &EvolveConfig,
+        _reporter: &mut SR,
+        _rng: &mut R,
+    ) {
+        if let (Some(deadline), Some(max_stale_generations)) =
+            (self.deadline, config.max_stale_generations)
+        {
+            if Instant::now() >= deadline {
+                state.stale_generations = state.stale_generations.max(max_stale_generations);
+            }
+        }
+    }
+}
+
+/// Wraps an island's fitness so a run starts from the island's previous population
+/// instead of random chromosomes and the greedy seed. Carried scores are kept, so only
+/// the rest of the population is evaluated again.
+#[derive(Clone, Debug)]
+struct IslandFitness<F> {
+    inner: F,
+    start_population: Vec<ScoredGenes>,
+}
+
+impl<F: SeededFitness> Fitness for IslandFitness<F> {
+    type Genotype = RangeGenotype<u16>;
+
+    fn call_for_state_population<S: StrategyState<Self::Genotype>, C: StrategyConfig>(
+        &mut self,
+        genotype: &Self::Genotype,
+        state: &mut S,
+        config: &C,
+        thread_local: Option<&ThreadLocal<RefCell<Self>>>,
+    ) {
+        if state.current_generation() == 0 {
+            let population = state.population_as_mut();
+            for (chromosome, (genes, score)) in population
+                .chromosomes
+                .iter_mut()
+                .zip(&self.start_population)
+            {
+                chromosome.genes.clone_from(genes);
+                chromosome.reset_metadata(false);
+                chromosome.set_fitness_score(*score);
+            }
+        }
+
+        let now = Instant::now();
+        self.call_for_population(
+            state.population_as_mut(),
+            genotype,
+            thread_local,
+            config.fitness_cache(),
+        );
+        state.add_duration(StrategyAction::Fitness, now.elapsed());
+    }
+
+    fn calculate_for_chromosome(
+        &mut self,
+        chromosome: &Chromosome<u16>,
+        genotype: &Self::Genotype,
+    ) -> Option<FitnessValue> {
+        self.inner.calculate_for_chromosome(chromosome, genotype)
+    }
+}
+
+/// Keeps the last `IMPROVEMENT_TRAIL_SIZE` best chromosomes seen during the run and the
+/// last generation's genes, which the strategy clears before returning.
+#[derive(Clone)]
+struct PoolReporter {
+    enabled: bool,
+    improvements: Vec<Vec<u16>>,
+    final_population: Vec<ScoredGenes>,
+}
+
+impl StrategyReporter for PoolReporter {
+    type Genotype = RangeGenotype<u16>;
+
+    fn on_new_best_chromosome<S: StrategyState<Self::Genotype>, C: StrategyConfig>(
+        &mut self,
+        _genotype: &Self::Genotype,
+        state: &S,
+        _config: &C,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        if let Some(genes) = state.best_genes() {
+            if self.improvements.len() == IMPROVEMENT_TRAIL_SIZE {
+                self.improvements.remove(0);
+            }
+            self.improvements.push(genes);
+        }
+    }
+
+    fn on_finish<S: StrategyState<Self::Genotype>, C: StrategyConfig>(
+        &mut self,
+        _genotype: &Self::Genotype,
+        state: &S,
+        _config: &C,
+    ) {
+        if self.enabled {
+            self.final_population = state
+                .population_as_ref()
+                .chromosomes
+                .iter()
+                .map(|chromosome| (chromosome.genes.clone(), chromosome.fitness_score()))
+                .collect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ga_parameters_scale_with_problem_size_and_budget() {
+        let day = GaParameters::scaled(96, 2, 0);
+        let month = GaParameters::scaled(2880, 40, 0);
+        assert_eq!(day.population_size, 88);
+        assert_eq!(month.population_size, GA_MAX_POPULATION_SIZE);
+        assert!(month.max_stale_generations > day.max_stale_generations);
+        assert!(month.mutation_count > day.mutation_count);
+        assert!(month.mutation_probability > day.mutation_probability);
+
+        // 100 generations of 2880 genes at 50ns each leave room for 69 chromosomes a second.
+        let budgeted = GaParameters::scaled(2880, 40, 1_000);
+        assert_eq!(budgeted.population_size, 69);
+    }
+
+    #[test]
+    fn ga_parameter_overrides_are_validated() {
+        let scaled = GaParameters::scaled(96, 2, 0);
+        assert_eq!(scaled.validate(1), Ok(()));
+        let two_islands = GaParameters {
+            population_size: 2 * ISLAND_MIN_POPULATION_SIZE,
+            ..scaled
+        };
+        assert_eq!(two_islands.validate(2), Ok(()));
+        assert!(two_islands.validate(3).is_err());
+        for invalid in [
+            GaParameters {
+                population_size: 0,
+                ..scaled
+            },
+            GaParameters {
+                mutation_count: 0,
+                ..scaled
+            },
+            GaParameters {
+                mutation_probability: 1.5,
+                ..scaled
+            },
+            GaParameters {
+                mutation_probability: f32::NAN,
+                ..scaled
+            },
+        ] {
+            assert!(invalid.validate(1).is_err(), "accepted {:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn migration_moves_each_elite_to_the_next_island() {
+        // Final populations come back in no particular order.
+        let mut populations = vec![
+            vec![
+                (vec![0, 0], Some(0)),
+                (vec![0, 1], None),
+                (vec![0, 2], Some(5)),
+            ],
+            vec![(vec![1, 0], Some(0)), (vec![1, 1], Some(1))],
+            vec![(vec![2, 0], Some(1)), (vec![2, 1], Some(0))],
+        ];
+        let elites = vec![
+            Some((vec![0, 9], Some(9))),
+            None,
+            Some((vec![2, 9], Some(5))),
+        ];
+
+        migrate(&mut populations, elites, 2);
+
+        assert_eq!(
+            populations[1],
+            vec![(vec![0, 9], Some(9)), (vec![1, 1], Some(1))]
+        );
+        assert_eq!(
+            populations[0],
+            vec![(vec![2, 9], Some(5)), (vec![0, 2], Some(5))]
+        );
+        assert_eq!(
+            populations[2],
+            vec![(vec![2, 0], Some(1)), (vec![2, 1], Some(0))]
+        );
+    }
+}
@@ -1,11 +1,23 @@
-use super::types::{GlobalConstraint, Problem, TimeSlot};
+use super::types::{GlobalConstraint, ParticipantId, Problem, TimeScope, TimeSlot};
+use serde::{Deserialize, Serialize};
 
-fn build_forbidden_slot_mask(problem: &Problem) -> Vec<bool> {
+const SLOTS_PER_DAY: u16 = 96;
+
+// Slots inside forbidden zones; zones tied to one activity are skipped when `global_only`.
+fn build_forbidden_slot_mask(problem: &Problem, global_only: bool) -> Vec<bool> {
     let total_slots = problem.total_slots as usize;
     let mut mask = vec![false; total_slots];
 
     for constraint in &problem.global_constraints {
-        if let GlobalConstraint::ForbiddenZone { start, end, .. } = constraint {
+        if let GlobalConstraint::ForbiddenZone {
+            start,
+            end,
+            activity_id,
+        } = constraint
+        {
+            if global_only && activity_id.is_some() {
+                continue;
+            }
             let start_index = (*start as usize).min(total_slots);
             let end_index = (*end as usize).min(total_slots);
             if start_index < end_index {
@@ -19,7 +31,13 @@ fn build_forbidden_slot_mask(problem: &Problem) -> Vec<bool> {
     mask
 }
 
-fn build_fixed_occupancy_mask(problem: &Problem) -> Vec<bool> {
+// Slots occupied by fixed events of `participant`, or of anyone when `None`. With
+// `skip_overlappable`, events some floating activity may overlap are left open.
+fn build_fixed_occupancy_mask(
+    problem: &Problem,
+    participant: Option<ParticipantId>,
+    skip_overlappable: bool,
+) -> Vec<bool> {
     let total_slots = problem.total_slots as usize;
     let mut mask = vec![false; total_slots];
 
@@ -32,6 +50,19 @@ fn build_fixed_occupancy_mask(problem: &Problem) -> Vec<bool> {
             Some(start) => start as usize,
             None => continue,
         };
+        if participant.is_some_and(|id| !activity.involves(id)) {
+            continue;
+        }
+        // Fixed events that some floating activity may overlap stay open as start slots.
+        let overlappable = skip_overlappable
+            && problem
+                .floating_indices
+                .iter()
+                .filter_map(|&idx| problem.activities.get(idx))
+                .any(|floating| floating.can_overlap(activity));
+        if overlappable {
+            continue;
+        }
         if start_time >= total_slots {
             continue;
         }
@@ -49,31 +80,120 @@ fn build_fixed_occupancy_mask(problem: &Problem) -> Vec<bool> {
     mask
 }
 
-pub(crate) fn build_candidate_start_slots(problem: &Problem) -> Vec<TimeSlot> {
+/// Start slots open to floating activities. With several participants, a slot is
+/// repeated once per participant who is free there, so different participants can
+/// start activities in the same slot.
+pub fn build_candidate_start_slots(problem: &Problem) -> Vec<TimeSlot> {
     let total_slots = problem.total_slots as usize;
     if total_slots == 0 {
         return vec![];
     }
 
-    let forbidden_mask = build_forbidden_slot_mask(problem);
-    let fixed_mask = build_fixed_occupancy_mask(problem);
-    let mut candidates = Vec::with_capacity(total_slots);
+    let forbidden_mask = build_forbidden_slot_mask(problem, false);
+    let fixed_masks: Vec<Vec<bool>> = if problem.participants.is_empty() {
+        vec![build_fixed_occupancy_mask(problem, None, true)]
+    } else {
+        problem
+            .participants
+            .iter()
+            .map(|participant| build_fixed_occupancy_mask(problem, Some(participant.id), true))
+            .collect()
+    };
+    let mut candidates = Vec::with_capacity(total_slots * fixed_masks.len());
 
     for slot in 0..total_slots {
-        if forbidden_mask[slot] || fixed_mask[slot] {
+        if forbidden_mask[slot] {
             continue;
         }
-        candidates.push(slot as TimeSlot);
+        for fixed_mask in &fixed_masks {
+            if !fixed_mask[slot] {
+                candidates.push(slot as TimeSlot);
+            }
+        }
     }
 
     candidates
 }
 
+/// Maximal run of free slots within one calendar day, as `[start, end)`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FreeInterval {
+    pub day: usize,
+    pub start: TimeSlot,
+    pub end: TimeSlot,
+    pub length: u16,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FreeIntervalFilter {
+    /// Drop intervals shorter than this many slots.
+    #[serde(default)]
+    pub min_length: u16,
+    /// Slot-of-day window `[from, to)`; wraps past midnight when `from > to`.
+    #[serde(default)]
+    pub time_of_day: Option<(TimeSlot, TimeSlot)>,
+    /// Only consider this participant's fixed events; `None` treats every fixed event as busy.
+    #[serde(default)]
+    pub participant: Option<ParticipantId>,
+}
+
+/// Free intervals per day: slots outside global forbidden zones and fixed events, using the
+/// same masks as candidate generation. Day boundaries follow `day_start_offset`.
+pub fn free_intervals(problem: &Problem, filter: &FreeIntervalFilter) -> Vec<FreeInterval> {
+    let total_slots = problem.total_slots as usize;
+    let forbidden_mask = build_forbidden_slot_mask(problem, true);
+    let fixed_mask = build_fixed_occupancy_mask(problem, filter.participant, false);
+    let in_window = |slot: TimeSlot| match filter.time_of_day {
+        None => true,
+        Some((from, to)) => {
+            let slot_of_day =
+                ((slot as u32 + problem.day_start_offset as u32) % SLOTS_PER_DAY as u32) as u16;
+            if from <= to {
+                slot_of_day >= from && slot_of_day < to
+            } else {
+                slot_of_day >= from || slot_of_day < to
+            }
+        }
+    };
+
+    let mut intervals = Vec::new();
+    let mut open: Option<(usize, TimeSlot)> = None;
+    for slot in 0..=total_slots {
+        let time_slot = slot as TimeSlot;
+        let day = problem.bucket_of(TimeScope::SameDay, time_slot);
+        let free = slot < total_slots
+            && !forbidden_mask[slot]
+            && !fixed_mask[slot]
+            && in_window(time_slot);
+
+        if let Some((open_day, start)) = open {
+            if !free || open_day != day {
+                let length = time_slot - start;
+                if length >= filter.min_length.max(1) {
+                    intervals.push(FreeInterval {
+                        day: open_day,
+                        start,
+                        end: time_slot,
+                        length,
+                    });
+                }
+                open = None;
+            }
+        }
+        if free && open.is_none() {
+            open = Some((day, time_slot));
+        }
+    }
+
+    intervals
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::solver::types::{
-        Activity, ActivityType, Binding, FrequencyTarget, Problem, UserFrequencyConstraint,
+        Activity, ActivityType, Binding, FrequencyTarget, Participant, Problem,
+        UserFrequencyConstraint,
     };
 
     fn base_activity(id: usize, kind: ActivityType, start: Option<u16>, duration: u16) -> Activity {
@@ -88,6 +208,14 @@ mod tests {
             output_bindings: Vec::<Binding>::new(),
             frequency_targets: Vec::<FrequencyTarget>::new(),
             user_frequency_constraints: Vec::<UserFrequencyConstraint>::new(),
+            occurrence_spacing: vec![],
+            buffer_before: 0,
+            buffer_after: 0,
+            location_id: None,
+            resource_requirements: vec![],
+            attention_free: false,
+            overlap_compatible_categories: vec![],
+            participant_ids: vec![],
         }
     }
 
@@ -108,10 +236,133 @@ mod tests {
             heatmap: vec![],
             markov_matrix: vec![],
             total_slots: 10,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
 
         let candidates = build_candidate_start_slots(&problem);
         // Excluded: forbidden [1,2] and fixed occupancy [4,5,6].
         assert_eq!(candidates, vec![0, 3, 7, 8, 9]);
     }
+
+    #[test]
+    fn fixed_slots_stay_open_when_a_floating_activity_may_overlap() {
+        let mut podcast = base_activity(0, ActivityType::Floating, None, 2);
+        podcast.attention_free = true;
+        let commute = base_activity(1, ActivityType::Fixed, Some(4), 3);
+
+        let problem = Problem {
+            activities: vec![podcast, commute],
+            floating_indices: vec![0],
+            fixed_indices: vec![1],
+            global_constraints: vec![],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 8,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidates = build_candidate_start_slots(&problem);
+        assert_eq!(candidates, (0..8).collect::<Vec<u16>>());
+    }
+
+    #[test]
+    fn each_free_participant_gets_a_start_lane() {
+        let floating = base_activity(0, ActivityType::Floating, None, 1);
+        let mut school = base_activity(1, ActivityType::Fixed, Some(1), 2);
+        school.participant_ids = vec![1];
+        let family_event = base_activity(2, ActivityType::Fixed, Some(4), 1);
+
+        let problem = Problem {
+            activities: vec![floating, school, family_event],
+            floating_indices: vec![0],
+            fixed_indices: vec![1, 2],
+            global_constraints: vec![],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 5,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![
+                Participant {
+                    id: 0,
+                    name: "Parent".to_string(),
+                },
+                Participant {
+                    id: 1,
+                    name: "Child".to_string(),
+                },
+            ],
+        };
+
+        let candidates = build_candidate_start_slots(&problem);
+        // Slots 1-2 only open for the parent; slot 4 is blocked for everyone.
+        assert_eq!(candidates, vec![0, 0, 1, 2, 3, 3]);
+    }
+
+    #[test]
+    fn free_intervals_split_at_midnight_and_respect_filters() {
+        let floating = base_activity(0, ActivityType::Floating, None, 2);
+        let fixed = base_activity(1, ActivityType::Fixed, Some(10), 4);
+        let gym_only = base_activity(2, ActivityType::Floating, None, 2);
+
+        let problem = Problem {
+            activities: vec![floating, fixed, gym_only],
+            floating_indices: vec![0, 2],
+            fixed_indices: vec![1],
+            global_constraints: vec![
+                GlobalConstraint::ForbiddenZone {
+                    start: 0,
+                    end: 4,
+                    activity_id: None,
+                },
+                GlobalConstraint::ForbiddenZone {
+                    start: 20,
+                    end: 30,
+                    activity_id: Some(2),
+                },
+            ],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 120,
+            day_start_offset: 80,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let all = free_intervals(&problem, &FreeIntervalFilter::default());
+        let spans: Vec<(usize, u16, u16)> = all.iter().map(|i| (i.day, i.start, i.end)).collect();
+        // Midnight falls at slot 16; the activity-specific zone does not make time busy.
+        assert_eq!(
+            spans,
+            vec![(0, 4, 10), (0, 14, 16), (1, 16, 112), (2, 112, 120)]
+        );
+
+        let filter = FreeIntervalFilter {
+            min_length: 4,
+            time_of_day: Some((8, 40)),
+            participant: None,
+        };
+        let mornings = free_intervals(&problem, &filter);
+        let spans: Vec<(u16, u16, u16)> = mornings
+            .iter()
+            .map(|i| (i.start, i.end, i.length))
+            .collect();
+        assert_eq!(spans, vec![(24, 56, 32)]);
+    }
 }
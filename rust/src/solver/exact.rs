@@ -0,0 +1,393 @@
+use super::fitness::{DiemFitness, FitnessBreakdown, TierRank};
+use super::greedy::greedy_schedule;
+use super::local_search::evaluate_indexed;
+use super::occurrence::occurrence_budget;
+use super::types::{ActivityId, TimeSlot};
+
+/// Best schedule of the exhaustive search, optimal within its search space only.
+#[derive(Debug, Clone)]
+pub struct ExactSolution {
+    pub placements: Vec<(ActivityId, TimeSlot)>,
+    pub breakdown: FitnessBreakdown,
+    /// Search nodes visited, each one a scored partial schedule.
+    pub nodes: usize,
+}
+
+/// Branch-and-bound over every schedule whose floating occurrences stay clear of each
+/// other, of conflicting fixed events (buffers included), of forbidden zones and of the
+/// horizon end, with up to each activity's occurrence budget (see `occurrence_budget`).
+/// Returns `None` when the search would visit more than `node_limit` nodes, so a result
+/// is optimal under the lexicographic objective within that space. Schedules with more
+/// occurrences than the budgets, or with conflicting ones, are not searched and may
+/// score higher.
+///
+/// Every node is a complete schedule (unvisited activities get no occurrences) and is
+/// scored with the full objective. Its bound drops every penalty and adds, per occurrence
+/// still allowed, the most priority, heatmap and adjacency reward it could bring, plus
+/// every frequency-target reward.
+pub fn solve_exact(fitness: &DiemFitness, node_limit: usize) -> Option<ExactSolution> {
+    let problem = &fitness.problem;
+    let mut start_slots = fitness.candidate_start_slots.clone();
+    start_slots.dedup();
+
+    let floating = problem.floating_indices.clone();
+    let mut options = Vec::with_capacity(floating.len());
+    let mut budgets = Vec::with_capacity(floating.len());
+    let mut gains = Vec::with_capacity(floating.len());
+    for &act_idx in &floating {
+        let activity = &problem.activities[act_idx];
+        let mut starts: Vec<TimeSlot> = start_slots
+            .iter()
+            .copied()
+            .filter(|&start| clear_of_fixed(fitness, act_idx, start))
+            .collect();
+        // Likely slots first, so good schedules are found early and prune more.
+        let heat = |start: TimeSlot| {
+            problem
+                .heatmap
+                .iter()
+                .filter(|&&(id, slot, _)| id == activity.id && slot == start)
+                .map(|&(_, _, prob)| prob)
+                .fold(0.0f32, f32::max)
+        };
+        starts.sort_by(|&a, &b| heat(b).total_cmp(&heat(a)).then(a.cmp(&b)));
+        options.push(starts);
+        budgets.push(occurrence_budget(problem, activity));
+
+        let heatmap_gain = problem
+            .heatmap
+            .iter()
+            .filter(|&&(id, _, _)| id == activity.id)
+            .map(|&(_, _, prob)| prob)
+            .fold(0.0f32, f32::max)
+            * DiemFitness::WEIGHT_HEATMAP;
+        let markov_gain = problem
+            .markov_matrix
+            .iter()
+            .filter(|&&(from, to, _)| from == activity.id || to == activity.id)
+            .map(|&(_, _, prob)| prob)
+            .fold(0.0f32, f32::max)
+            * DiemFitness::WEIGHT_MARKOV;
+        let batching_gain = problem
+            .category_batching_bonus
+            .iter()
+            .filter(|&&(category_id, _)| category_id == activity.category_id)
+            .map(|&(_, bonus)| bonus)
+            .fold(0.0f32, f32::max);
+        // An occurrence gains at most one adjacency on either side.
+        gains.push(heatmap_gain + 2.0 * (markov_gain + batching_gain));
+    }
+
+    let frequency_ceiling = problem
+        .activities
+        .iter()
+        .flat_map(|activity| &activity.frequency_targets)
+        .map(|target| target.weight.max(0.0) * problem.bucket_count(target.scope) as f32)
+        .sum();
+
+    let mut search = Search {
+        fitness,
+        floating,
+        options,
+        budgets,
+        gains,
+        frequency_ceiling,
+        placed: Vec::new(),
+        nodes: 0,
+        node_limit,
+        best: None,
+    };
+
+    // The greedy schedule gives the bound something to beat from the first node.
+    let index_of = |activity_id: ActivityId| {
+        problem
+            .activities
+            .iter()
+            .position(|activity| activity.id == activity_id)
+    };
+    let greedy: Vec<(usize, TimeSlot)> = greedy_schedule(problem)
+        .into_iter()
+        .filter_map(|(activity_id, start)| index_of(activity_id).map(|idx| (idx, start)))
+        .collect();
+    let greedy_breakdown = evaluate_indexed(fitness, &greedy, fitness.candidate_start_slots.len());
+    search.best = Some((
+        greedy_breakdown.lexicographic_rank(),
+        greedy,
+        greedy_breakdown,
+    ));
+
+    if !search.visit(0, 0, 0) {
+        return None;
+    }
+    let nodes = search.nodes;
+    let (_, placements, breakdown) = search.best?;
+    let mut placements: Vec<(ActivityId, TimeSlot)> = placements
+        .into_iter()
+        .map(|(act_idx, start)| (problem.activities[act_idx].id, start))
+        .collect();
+    placements.sort_unstable_by_key(|&(_, start)| start);
+    Some(ExactSolution {
+        placements,
+        breakdown,
+        nodes,
+    })
+}
+
+struct Search<'a> {
+    fitness: &'a DiemFitness,
+    floating: Vec<usize>,
+    /// Allowed start slots per floating position.
+    options: Vec<Vec<TimeSlot>>,
+    budgets: Vec<usize>,
+    /// Optimistic non-priority reward of one more occurrence per floating position.
+    gains: Vec<f32>,
+    frequency_ceiling: f32,
+    placed: Vec<(usize, TimeSlot)>,
+    nodes: usize,
+    node_limit: usize,
+    best: Option<Incumbent>,
+}
+
+// Best schedule so far as `(rank, placements, breakdown)`.
+type Incumbent = (TierRank, Vec<(usize, TimeSlot)>, FitnessBreakdown);
+
+impl Search<'_> {
+    // Scores the current schedule, then extends it with occurrences of the floating
+    // activity at `pos` (from option `next`, `count` placed so far) or of later ones.
+    // Returns false once the node limit is exceeded.
+    fn visit(&mut self, pos: usize, next: usize, count: usize) -> bool {
+        self.nodes += 1;
+        if self.nodes > self.node_limit {
+            return false;
+        }
+
+        let breakdown = evaluate_indexed(
+            self.fitness,
+            &self.placed,
+            self.fitness.candidate_start_slots.len(),
+        );
+        let rank = breakdown.lexicographic_rank();
+        if self.best.as_ref().is_none_or(|(best, _, _)| rank > *best) {
+            self.best = Some((rank, self.placed.clone(), breakdown.clone()));
+        }
+        let best_rank = self.best.as_ref().map(|(best, _, _)| *best);
+        if best_rank.is_some_and(|best| self.upper_bound(&breakdown, pos, count) <= best) {
+            return true;
+        }
+
+        for p in pos..self.floating.len() {
+            let (from, placed_count) = if p == pos { (next, count) } else { (0, 0) };
+            if placed_count >= self.budgets[p] {
+                continue;
+            }
+            for i in from..self.options[p].len() {
+                let start = self.options[p][i];
+                if !self.clear_of_placed(self.floating[p], start) {
+                    continue;
+                }
+                self.placed.push((self.floating[p], start));
+                let within_limit = self.visit(p, i + 1, placed_count + 1);
+                self.placed.pop();
+                if !within_limit {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    fn upper_bound(&self, breakdown: &FitnessBreakdown, pos: usize, count: usize) -> TierRank {
+        let problem = &self.fitness.problem;
+        let mut soft = breakdown.priority_reward
+            + breakdown.habit_reward
+            + breakdown.idle_reward
+            + self.frequency_ceiling;
+        for p in pos..self.floating.len() {
+            let already = if p == pos { count } else { 0 };
+            let priority = problem.activities[self.floating[p]].priority.max(0.0);
+            for k in already..self.budgets[p] {
+                soft += priority
+                    * DiemFitness::WEIGHT_PRIORITY
+                    * DiemFitness::PRIORITY_REPEAT_DECAY.powi(k as i32)
+                    + self.gains[p];
+            }
+        }
+        FitnessBreakdown {
+            priority_reward: soft,
+            ..FitnessBreakdown::default()
+        }
+        .lexicographic_rank()
+    }
+
+    fn clear_of_placed(&self, act_idx: usize, start: TimeSlot) -> bool {
+        let problem = &self.fitness.problem;
+        let activity = &problem.activities[act_idx];
+        self.placed.iter().all(|&(other_idx, other_start)| {
+            let other = &problem.activities[other_idx];
+            !other.shares_participants(activity)
+                || other.can_overlap(activity)
+                || other.clear_with_buffers(other_start, activity, start)
+        })
+    }
+}
+
+// Whether an occurrence at `start` fits the horizon, forbidden zones and fixed events.
+fn clear_of_fixed(fitness: &DiemFitness, act_idx: usize, start: TimeSlot) -> bool {
+    let problem = &fitness.problem;
+    let activity = &problem.activities[act_idx];
+    let end = start as u32 + activity.duration_slots as u32;
+    if end > problem.total_slots as u32 {
+        return false;
+    }
+    if problem.in_forbidden_zone(activity.id, start, activity.duration_slots) {
+        return false;
+    }
+    problem.fixed_indices.iter().all(|&idx| {
+        let fixed = &problem.activities[idx];
+        let Some(fixed_start) = fixed.assigned_start else {
+            return true;
+        };
+        !fixed.shares_participants(activity)
+            || fixed.can_overlap(activity)
+            || fixed.clear_with_buffers(fixed_start, activity, start)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::candidate_slots::build_candidate_start_slots;
+    use crate::solver::local_search::evaluate;
+    use crate::solver::types::{
+        Activity, ActivityType, Binding, FrequencyTarget, GlobalConstraint, Problem, TimeScope,
+        UserFrequencyConstraint,
+    };
+    use crate::solver::{solve_with_config, SolverConfig};
+
+    fn base_activity(id: usize, kind: ActivityType) -> Activity {
+        Activity {
+            id,
+            activity_type: kind,
+            duration_slots: 3,
+            priority: 1.0,
+            assigned_start: None,
+            category_id: 0,
+            input_bindings: Vec::<Binding>::new(),
+            output_bindings: Vec::<Binding>::new(),
+            frequency_targets: Vec::<FrequencyTarget>::new(),
+            user_frequency_constraints: vec![UserFrequencyConstraint {
+                scope: TimeScope::SameDay,
+                min_count: None,
+                max_count: Some(1),
+                deadline_end: None,
+                penalty_weight: 100.0,
+            }],
+            occurrence_spacing: vec![],
+            buffer_before: 0,
+            buffer_after: 0,
+            location_id: None,
+            resource_requirements: vec![],
+            attention_free: false,
+            overlap_compatible_categories: vec![],
+            participant_ids: vec![],
+        }
+    }
+
+    #[test]
+    fn matches_brute_force_over_every_pair_of_starts() {
+        let mut meeting = base_activity(0, ActivityType::Fixed);
+        meeting.assigned_start = Some(8);
+        meeting.user_frequency_constraints.clear();
+        let mut gym = base_activity(1, ActivityType::Floating);
+        gym.buffer_after = 1;
+        let mut reading = base_activity(2, ActivityType::Floating);
+        reading.category_id = 1;
+
+        let problem = Problem {
+            activities: vec![meeting, gym, reading],
+            floating_indices: vec![1, 2],
+            fixed_indices: vec![0],
+            global_constraints: vec![],
+            heatmap: vec![(1, 6, 0.9), (1, 2, 0.6), (2, 7, 0.8), (2, 12, 0.5)],
+            markov_matrix: vec![(1, 2, 0.9), (0, 2, 0.4)],
+            total_slots: 20,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let fitness = DiemFitness::new(problem.clone(), candidate_slots);
+
+        let solution = solve_exact(&fitness, 100_000).expect("search should finish");
+
+        let mut best =
+            evaluate(&fitness, &[], fitness.candidate_start_slots.len()).lexicographic_score();
+        for gym_start in 0..20 {
+            for reading_start in 0..20 {
+                for placements in [
+                    vec![(1, gym_start)],
+                    vec![(2, reading_start)],
+                    vec![(1, gym_start), (2, reading_start)],
+                ] {
+                    best = best.max(
+                        evaluate(&fitness, &placements, fitness.candidate_start_slots.len())
+                            .lexicographic_score(),
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            solution.breakdown.lexicographic_score(),
+            best,
+            "got {:?}",
+            solution.placements
+        );
+
+        // Small enough for `solve_with_config` to take the exact path.
+        let candidates = solve_with_config(problem, &SolverConfig::default()).unwrap();
+        assert!(candidates[0].proven_optimal);
+        assert_eq!(candidates[0].fitness, best);
+    }
+
+    #[test]
+    fn hard_violations_fall_through_to_the_backend() {
+        let gym = base_activity(0, ActivityType::Floating);
+        let problem = Problem {
+            activities: vec![gym],
+            floating_indices: vec![0],
+            fixed_indices: vec![],
+            // Three occurrences' worth, but the daily cap lets the exact search place one.
+            global_constraints: vec![GlobalConstraint::CumulativeTime {
+                activity_id: Some(0),
+                category_id: None,
+                period_slots: 20,
+                min_duration: 9,
+                max_duration: 20,
+                deadline_end: Some(20),
+            }],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 20,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let fitness = DiemFitness::new(problem.clone(), candidate_slots);
+        let solution = solve_exact(&fitness, 100_000).expect("search should finish");
+        assert!(solution.breakdown.hard_violations > 0);
+
+        let config = SolverConfig {
+            max_generations: 20,
+            ..SolverConfig::default()
+        };
+        let candidates = solve_with_config(problem, &config).unwrap();
+        assert!(!candidates[0].proven_optimal);
+    }
+}
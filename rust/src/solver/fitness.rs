@@ -1,50 +1,464 @@
-use crate::solver::types::{ActivityId, GlobalConstraint, Problem, TimeScope, TimeSlot};
+use crate::solver::backend::SeededFitness;
+use crate::solver::greedy::greedy_schedule;
+use crate::solver::operators::ScheduleLayout;
+use crate::solver::types::{
+    Activity, ActivityId, ActivitySelector, CategoryId, ExclusionWindow, GlobalConstraint,
+    HeatmapTable, LocationId, MarkovTable, Problem, ResourceId, TimeScope, TimeSlot,
+};
 use genetic_algorithm::chromosome::Chromosome;
 use genetic_algorithm::fitness::{Fitness, FitnessValue};
 use genetic_algorithm::genotype::RangeGenotype;
 use genetic_algorithm::strategy::{StrategyAction, StrategyConfig, StrategyState};
+use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
+use std::cmp::Reverse;
 use std::collections::HashMap;
+use std::ops::Range;
 use std::time::Instant;
 use thread_local::ThreadLocal;
 
+// One occurrence on the timeline with its calendar buckets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ScheduledItem {
+    act_idx: usize,
+    start: TimeSlot,
+    end: TimeSlot,
+    day: usize,
+    week: usize,
+    weekday: usize,
+}
+
+impl ScheduledItem {
+    fn new(problem: &Problem, act_idx: usize, start: TimeSlot) -> Self {
+        let day = problem.bucket_of(TimeScope::SameDay, start);
+        Self {
+            act_idx,
+            start,
+            end: start + problem.activities[act_idx].duration_slots,
+            day,
+            week: problem.bucket_of(TimeScope::SameWeek, start),
+            weekday: day % 7,
+        }
+    }
+
+    // Sweep order; ties go by activity so merged and incremental schedules agree.
+    fn order_key(&self) -> (TimeSlot, usize) {
+        (self.start, self.act_idx)
+    }
+}
+
+// An already-swept occurrence, kept per activity in start order.
+#[derive(Clone, Copy, Debug)]
+struct SeenOccurrence {
+    day: usize,
+    week: usize,
+    end: TimeSlot,
+}
+
+// Occurrences whose `bucket` matches and whose end, capped at the horizon, is at most
+// `deadline`. Start order keeps both buckets and ends non-decreasing.
+fn count_ends_leq(
+    seen: &[SeenOccurrence],
+    bucket_of: impl Fn(&SeenOccurrence) -> usize,
+    bucket: usize,
+    deadline: TimeSlot,
+    total_slots: TimeSlot,
+) -> u32 {
+    let from = seen.partition_point(|occurrence| bucket_of(occurrence) < bucket);
+    let to = seen.partition_point(|occurrence| {
+        (bucket_of(occurrence), occurrence.end.min(total_slots)) <= (bucket, deadline)
+    });
+    to.saturating_sub(from) as u32
+}
+
+// Forbidden zones as separately sorted starts and ends, so the zones an interval meets
+// are counted with two binary searches. Empty zones cover no slot.
 #[derive(Clone, Debug, Default)]
-struct DurationPrefixIndex {
+struct ZoneIndex {
+    starts: Vec<TimeSlot>,
     ends: Vec<TimeSlot>,
-    prefix_sum: Vec<u32>,
 }
 
-impl DurationPrefixIndex {
-    fn from_events(mut events: Vec<(TimeSlot, u16)>) -> Self {
-        if events.is_empty() {
-            return Self::default();
+impl ZoneIndex {
+    fn push(&mut self, start: TimeSlot, end: TimeSlot) {
+        if start < end {
+            self.starts.push(start);
+            self.ends.push(end);
+        }
+    }
+
+    fn sort(&mut self) {
+        self.starts.sort_unstable();
+        self.ends.sort_unstable();
+    }
+
+    // Zones overlapping `[start, end)`: those begun before `end` less those over by `start`.
+    fn overlapping(&self, start: TimeSlot, end: TimeSlot) -> u32 {
+        let begun = self.starts.partition_point(|&zone_start| zone_start < end);
+        let over = self.ends.partition_point(|&zone_end| zone_end <= start);
+        begun.saturating_sub(over) as u32
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum CumulativeTarget {
+    Activity(ActivityId),
+    Category(CategoryId),
+    Global,
+    // An activity paired with a category it does not belong to.
+    Nothing,
+}
+
+impl CumulativeTarget {
+    fn matches(&self, activity: &Activity) -> bool {
+        match self {
+            CumulativeTarget::Activity(id) => activity.id == *id,
+            CumulativeTarget::Category(id) => activity.category_id == *id,
+            CumulativeTarget::Global => true,
+            CumulativeTarget::Nothing => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct CumulativeRule {
+    target: CumulativeTarget,
+    // Counts occurrences ending by the deadline in one bucket, else buckets of `period_slots`.
+    deadline_end: Option<TimeSlot>,
+    period_slots: u16,
+    // This rule's buckets in the flat cumulative totals.
+    buckets: Range<usize>,
+    min_duration: u32,
+    max_duration: u32,
+}
+
+#[derive(Clone, Debug)]
+struct PrecedenceEdge {
+    predecessor_id: ActivityId,
+    min_lag: u16,
+    max_lag: Option<u16>,
+    weight: f32,
+}
+
+#[derive(Clone, Debug)]
+struct ExclusionRule {
+    first: ActivitySelector,
+    second: ActivitySelector,
+    window: ExclusionWindow,
+    weight: f32,
+}
+
+// Occurrences matching an exclusion rule's selectors so far in the current scope bucket.
+#[derive(Clone, Copy, Debug, Default)]
+struct ExclusionCounts {
+    bucket: usize,
+    first: u32,
+    second: u32,
+    both: u32,
+}
+
+#[derive(Clone, Debug)]
+struct BusyLimitRule {
+    category_id: Option<CategoryId>,
+    max_busy_slots: u16,
+    min_break_slots: u16,
+    weight: f32,
+}
+
+impl BusyLimitRule {
+    fn excess_penalty(&self, streak_start: TimeSlot, streak_end: TimeSlot) -> f32 {
+        let length = streak_end.saturating_sub(streak_start);
+        length.saturating_sub(self.max_busy_slots) as f32 * self.weight
+    }
+}
+
+#[derive(Clone, Debug)]
+struct WorkloadRule {
+    scope: TimeScope,
+    category_id: Option<CategoryId>,
+    max_busy_slots: u16,
+    hard: bool,
+    penalty_weight: f32,
+}
+
+#[derive(Clone, Debug)]
+struct FreeTimeRule {
+    scope: TimeScope,
+    min_free_slots: u16,
+    weight: f32,
+    // Slots per bucket that are inside the horizon and not globally forbidden.
+    available_by_bucket: Vec<u32>,
+}
+
+// A busy stretch of the timeline: an occurrence with its category, or travel (`None`).
+#[derive(Clone, Copy, Debug)]
+struct BusyInterval {
+    start: TimeSlot,
+    end: TimeSlot,
+    category_id: Option<CategoryId>,
+}
+
+// Calls `f` with the union of a participant's occurrences and travel, which is the time
+// neither `free_slots` nor free-time targets count as free.
+fn for_each_occupied(intervals: &[BusyInterval], f: impl FnMut(TimeSlot, TimeSlot)) {
+    for_each_busy_union(intervals, |_| true, f);
+}
+
+// Calls `f` with disjoint pieces covering the union of the `intervals` (sorted by start)
+// that pass `include`, so slots shared by overlapping occurrences count once.
+fn for_each_busy_union(
+    intervals: &[BusyInterval],
+    include: impl Fn(&BusyInterval) -> bool,
+    mut f: impl FnMut(TimeSlot, TimeSlot),
+) {
+    let mut covered_until: TimeSlot = 0;
+    for interval in intervals.iter().filter(|interval| include(interval)) {
+        let from = interval.start.max(covered_until);
+        if interval.end > from {
+            f(from, interval.end);
+            covered_until = interval.end;
+        }
+    }
+}
+
+/// Adds the part of `[start, end)` that falls into each calendar bucket of `scope`.
+fn add_busy_by_bucket(
+    busy_by_bucket: &mut [u32],
+    problem: &Problem,
+    scope: TimeScope,
+    start: TimeSlot,
+    end: TimeSlot,
+) {
+    let offset = problem.day_start_offset as u32;
+    let bucket_slots = scope.bucket_slots();
+    let mut cursor = start as u32 + offset;
+    let end = end.min(problem.total_slots) as u32 + offset;
+    while cursor < end {
+        let bucket = cursor / bucket_slots;
+        let bucket_end = (bucket + 1).saturating_mul(bucket_slots).min(end);
+        if let Some(busy) = busy_by_bucket.get_mut(bucket as usize) {
+            *busy += bucket_end - cursor;
+        }
+        cursor = bucket_end;
+    }
+}
+
+/// Buffers `DiemFitness::score_items` reuses across evaluations on one thread, so a
+/// sweep allocates only when a problem needs more room than the last one.
+#[derive(Default)]
+struct SweepScratch {
+    total_day_counts: Vec<u16>,
+    total_week_counts: Vec<u16>,
+    seen_occurrences: Vec<Vec<SeenOccurrence>>,
+    busy_intervals: Vec<Vec<BusyInterval>>,
+    cumulative_totals: Vec<Option<u32>>,
+    busy_by_bucket: Vec<u32>,
+    demand_events: Vec<Vec<(TimeSlot, i32)>>,
+}
+
+thread_local! {
+    static SWEEP_SCRATCH: RefCell<SweepScratch> = RefCell::new(SweepScratch::default());
+}
+
+// Refills `buffer` with `len` copies of `value`, keeping its allocation.
+fn reset<T: Clone>(buffer: &mut Vec<T>, len: usize, value: T) {
+    buffer.clear();
+    buffer.resize(len, value);
+}
+
+// Resizes `buffers` to `len` empty vectors, keeping the allocations of those kept.
+fn reset_each<T>(buffers: &mut Vec<Vec<T>>, len: usize) {
+    buffers.truncate(len);
+    buffers.iter_mut().for_each(Vec::clear);
+    buffers.resize_with(len, Vec::new);
+}
+
+/// Components of one fitness evaluation. Penalties from `PENALTY_*` constants count as
+/// hard violations; user-weighted constraints and learned preferences are kept apart.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FitnessBreakdown {
+    /// Priority rewards of placed occurrences (with repeat decay).
+    pub priority_reward: f32,
+    /// Heatmap, Markov transition and category batching rewards.
+    pub habit_reward: f32,
+    /// Soft frequency-target rewards.
+    pub frequency_reward: f32,
+    /// Small reward for unused start slots.
+    pub idle_reward: f32,
+    pub hard_violations: u32,
+    pub hard_penalty: f32,
+    /// Bindings, frequency limits, precedence, exclusions, spacing, busy limits and
+    /// soft workload/free-time rules.
+    pub constraint_penalty: f32,
+    /// Soft frequency-target overshoot.
+    pub frequency_penalty: f32,
+    /// Category switch costs between adjacent activities.
+    pub switch_penalty: f32,
+    /// Horizon slots covered by no occurrence and no travel, summed over participants;
+    /// overlapping occurrences share their slots.
+    pub free_slots: u32,
+}
+
+impl FitnessBreakdown {
+    // Tier values are compared in tenths of a point.
+    const TIER_RESOLUTION: f32 = 10.0;
+
+    // `count` separate violations (overlapping pairs, zones hit), each with its penalty.
+    fn add_hard(&mut self, count: u32, penalty_each: f32) {
+        self.hard_violations = self.hard_violations.saturating_add(count);
+        self.hard_penalty += count as f32 * penalty_each;
+    }
+
+    // One violation of a size-based rule (excess slots, missing minutes), penalized per unit.
+    fn add_hard_excess(&mut self, units: u32, penalty_per_unit: f32) {
+        if units > 0 {
+            self.hard_violations = self.hard_violations.saturating_add(1);
+            self.hard_penalty += units as f32 * penalty_per_unit;
         }
+    }
+
+    /// Single weighted score: every penalty and reward summed by magnitude.
+    pub fn total(&self) -> f32 {
+        self.soft_score() - (self.hard_penalty + self.constraint_penalty)
+    }
+
+    /// Net soft-preference score (rewards minus learned-preference penalties).
+    pub fn soft_score(&self) -> f32 {
+        self.priority_reward + self.habit_reward + self.frequency_reward + self.idle_reward
+            - self.frequency_penalty
+            - self.switch_penalty
+    }
 
-        events.sort_unstable_by_key(|(end, _)| *end);
-        let mut ends = Vec::with_capacity(events.len());
-        let mut prefix_sum = Vec::with_capacity(events.len());
-        let mut running_total = 0u32;
-        for (end, duration) in events {
-            ends.push(end);
-            running_total += duration as u32;
-            prefix_sum.push(running_total);
+    /// Separate objectives for trade-off views; all of them are maximized.
+    pub fn objectives(&self) -> ParetoObjectives {
+        ParetoObjectives {
+            productivity: self.priority_reward,
+            habit_fit: self.habit_reward - self.switch_penalty,
+            adherence: self.frequency_reward - self.frequency_penalty,
+            free_time: self.free_slots as f32,
         }
+    }
+
+    /// Packs the three tiers into one integer for the GA's fitness value: fewer hard
+    /// violations first, then lower user-constraint penalty, then higher soft score. Tiers
+    /// saturate at their bit budget, so compare schedules with `lexicographic_rank`.
+    pub fn lexicographic_score(&self) -> isize {
+        self.tiered_score(self.soft_score())
+    }
 
-        Self { ends, prefix_sum }
+    /// The three tiers of `lexicographic_score`, compared field by field without limits.
+    pub fn lexicographic_rank(&self) -> TierRank {
+        self.tier_rank(self.soft_score())
     }
 
-    fn sum_leq(&self, deadline: TimeSlot) -> u32 {
-        let idx = self.ends.partition_point(|&end| end <= deadline);
-        if idx == 0 {
-            0
-        } else {
-            self.prefix_sum[idx - 1]
+    fn tier_rank(&self, soft_score: f32) -> TierRank {
+        TierRank {
+            hard: Reverse(self.hard_violations),
+            user: Reverse(Self::tier_value(self.constraint_penalty)),
+            soft: Self::tier_value(soft_score),
         }
     }
+
+    fn tier_value(value: f32) -> i64 {
+        (value * Self::TIER_RESOLUTION).round() as i64
+    }
+
+    // Lexicographic packing with a caller-chosen soft tier.
+    fn tiered_score(&self, soft_score: f32) -> isize {
+        const SOFT_BITS: u32 = 24;
+        const USER_BITS: u32 = 26;
+        const HARD_BITS: u32 = 12;
+        let hard = (self.hard_violations as i64).min((1 << HARD_BITS) - 1);
+        let user = Self::tier_value(self.constraint_penalty).clamp(0, (1 << USER_BITS) - 1);
+        let soft_limit = 1i64 << (SOFT_BITS - 1);
+        let soft = Self::tier_value(soft_score).clamp(-soft_limit, soft_limit - 1);
+        let packed = -(hard << (USER_BITS + SOFT_BITS)) - (user << SOFT_BITS) + soft;
+        packed as isize
+    }
+}
+
+/// Ordering of schedules by tier: fewer hard violations, then lower user-constraint
+/// penalty, then higher soft score, each in tenths of a point. Greater is better.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TierRank {
+    pub hard: Reverse<u32>,
+    pub user: Reverse<i64>,
+    pub soft: i64,
+}
+
+/// How `DiemFitness` folds a breakdown into the GA's single fitness value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ObjectiveMode {
+    /// Hard violations, then user constraints, then soft preferences.
+    #[default]
+    Lexicographic,
+    /// Legacy sum of penalty magnitudes and rewards.
+    Weighted,
+    /// Hard and user tiers as in `Lexicographic`, then a weighted sum of the Pareto
+    /// objectives instead of the default soft score.
+    Tradeoff(ParetoObjectives),
+}
+
+/// Objectives kept apart for the Pareto front; also used as weights for `Tradeoff`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParetoObjectives {
+    pub productivity: f32,
+    pub habit_fit: f32,
+    pub adherence: f32,
+    pub free_time: f32,
+}
+
+impl ParetoObjectives {
+    pub(crate) fn values(&self) -> [f32; 4] {
+        [
+            self.productivity,
+            self.habit_fit,
+            self.adherence,
+            self.free_time,
+        ]
+    }
+
+    pub fn weighted_sum(&self, weights: &ParetoObjectives) -> f32 {
+        self.values()
+            .iter()
+            .zip(weights.values())
+            .map(|(value, weight)| value * weight)
+            .sum()
+    }
+
+    /// At least as good in every objective and strictly better in one.
+    pub fn dominates(&self, other: &ParetoObjectives) -> bool {
+        let pairs = self.values().into_iter().zip(other.values());
+        pairs.clone().all(|(a, b)| a >= b) && pairs.into_iter().any(|(a, b)| a > b)
+    }
 }
 
-fn count_ends_leq(sorted_ends: &[TimeSlot], deadline: TimeSlot) -> u32 {
-    sorted_ends.partition_point(|&end| end <= deadline) as u32
+impl ObjectiveMode {
+    /// The GA fitness value; packed, so use `rank` to compare schedules.
+    pub fn score(&self, breakdown: &FitnessBreakdown) -> isize {
+        match self {
+            ObjectiveMode::Lexicographic => breakdown.lexicographic_score(),
+            ObjectiveMode::Weighted => breakdown.total() as isize,
+            ObjectiveMode::Tradeoff(weights) => {
+                breakdown.tiered_score(breakdown.objectives().weighted_sum(weights))
+            }
+        }
+    }
+
+    /// Orders breakdowns the way `score` intends, without its tier saturation.
+    pub fn rank(&self, breakdown: &FitnessBreakdown) -> TierRank {
+        match self {
+            ObjectiveMode::Lexicographic => breakdown.lexicographic_rank(),
+            ObjectiveMode::Weighted => TierRank {
+                hard: Reverse(0),
+                user: Reverse(0),
+                soft: FitnessBreakdown::tier_value(breakdown.total()),
+            },
+            ObjectiveMode::Tradeoff(weights) => {
+                breakdown.tier_rank(breakdown.objectives().weighted_sum(weights))
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -52,8 +466,39 @@ pub struct DiemFitness {
     pub problem: Problem,
     pub candidate_start_slots: Vec<TimeSlot>,
     pub no_activity_allele: u16,
-    pub heatmap_lookup: HashMap<(ActivityId, TimeSlot), f32>,
-    pub markov_lookup: HashMap<(ActivityId, ActivityId), f32>,
+    pub heatmap_table: HeatmapTable,
+    pub markov_table: MarkovTable,
+    pub switch_cost_lookup: HashMap<(CategoryId, CategoryId), f32>,
+    pub batching_bonus_lookup: HashMap<CategoryId, f32>,
+    pub travel_lookup: HashMap<(LocationId, LocationId), u16>,
+    pub objective: ObjectiveMode,
+    // Maps resource ids to their position in `problem.resources`.
+    resource_index: HashMap<ResourceId, usize>,
+    // Lanes (positions in `problem.participants`, or the single lane of a one-person
+    // problem) each activity index occupies.
+    activity_lanes: Vec<Vec<usize>>,
+    // Precedence edges grouped by successor activity id.
+    precedence_edges: Vec<Vec<PrecedenceEdge>>,
+    exclusion_rules: Vec<ExclusionRule>,
+    busy_limit_rules: Vec<BusyLimitRule>,
+    workload_rules: Vec<WorkloadRule>,
+    free_time_rules: Vec<FreeTimeRule>,
+    // Longest activity duration; bounds the backward scan for lag-window exclusions.
+    max_duration_slots: u16,
+    // Longest pre-activity buffer; bounds how long finished items stay in the overlap sweep.
+    max_buffer_before: u16,
+    // Zones forbidden to every activity, then to each activity index alone.
+    forbidden_zones: ZoneIndex,
+    activity_forbidden_zones: Vec<ZoneIndex>,
+    num_days: usize,
+    num_weeks: usize,
+    // Fixed events in sweep order, merged with the floating ones on every evaluation.
+    fixed_items: Vec<ScheduledItem>,
+    cumulative_rules: Vec<CumulativeRule>,
+    // Cumulative rules counting each activity index.
+    cumulative_rules_by_activity: Vec<Vec<usize>>,
+    // Cumulative totals before any occurrence, all rules' buckets back to back.
+    empty_cumulative_totals: Vec<Option<u32>>,
 }
 
 impl DiemFitness {
@@ -61,169 +506,349 @@ impl DiemFitness {
     const PENALTY_OVERLAP: f32 = 1_000_000.0;
     const PENALTY_FORBIDDEN: f32 = 1_000_000.0;
     const PENALTY_CUMULATIVE: f32 = 10_000.0;
+    const PENALTY_BUFFER: f32 = 10_000.0;
+    const PENALTY_WORKLOAD_HARD: f32 = 1_000_000.0;
+    const PENALTY_TRAVEL: f32 = 1_000_000.0;
+    const PENALTY_RESOURCE: f32 = 100_000.0; // per excess unit per slot
 
     // Weights (Soft Constraints/Objectives)
-    const WEIGHT_PRIORITY: f32 = 10.0;
-    const WEIGHT_HEATMAP: f32 = 100.0;
-    const WEIGHT_MARKOV: f32 = 5.0;
+    pub(crate) const WEIGHT_PRIORITY: f32 = 10.0;
+    pub(crate) const WEIGHT_HEATMAP: f32 = 100.0;
+    pub(crate) const WEIGHT_MARKOV: f32 = 5.0;
     const FREQUENCY_OVERSHOOT_MULTIPLIER: f32 = 100.0;
     const REWARD_NO_ACTIVITY: f32 = 0.01;
-    const PRIORITY_REPEAT_DECAY: f32 = 0.65;
+    pub(crate) const PRIORITY_REPEAT_DECAY: f32 = 0.65;
 
     const MARKOV_GAP_TOLERANCE: u16 = 2; // 30 minutes
+    const CONTEXT_SWITCH_GAP_TOLERANCE: u16 = 4; // 1 hour
 
     pub fn new(problem: Problem, candidate_start_slots: Vec<TimeSlot>) -> Self {
         let no_activity_allele = u16::try_from(problem.floating_indices.len()).unwrap_or(u16::MAX);
-        let (heatmap_lookup, markov_lookup) = problem.build_lookup_maps();
-        Self {
-            problem,
-            candidate_start_slots,
-            no_activity_allele,
-            heatmap_lookup,
-            markov_lookup,
+        let (heatmap_table, markov_table) = problem.build_lookup_tables();
+        let switch_cost_lookup = problem
+            .category_switch_costs
+            .iter()
+            .map(|&(from, to, cost)| ((from, to), cost))
+            .collect();
+        let batching_bonus_lookup = problem.category_batching_bonus.iter().copied().collect();
+        let mut travel_lookup = HashMap::<(LocationId, LocationId), u16>::new();
+        for &(from, to, slots) in &problem.travel_times {
+            travel_lookup.insert((from, to), slots);
         }
-    }
-}
-
-impl Fitness for DiemFitness {
-    type Genotype = RangeGenotype<u16>;
+        for &(from, to, slots) in &problem.travel_times {
+            travel_lookup.entry((to, from)).or_insert(slots);
+        }
+        let resource_index = problem
+            .resources
+            .iter()
+            .enumerate()
+            .map(|(index, resource)| (resource.id, index))
+            .collect();
 
-    fn call_for_state_population<S: StrategyState<Self::Genotype>, C: StrategyConfig>(
-        &mut self,
-        genotype: &Self::Genotype,
-        state: &mut S,
-        config: &C,
-        thread_local: Option<&ThreadLocal<RefCell<Self>>>,
-    ) {
-        if state.current_generation() == 0 {
-            let population = state.population_as_mut();
-            if let Some(chromosome) = population.chromosomes.first_mut() {
-                for gene in &mut chromosome.genes {
-                    *gene = self.no_activity_allele;
+        let num_activities = problem.activities.len();
+        let activity_lanes = problem
+            .activities
+            .iter()
+            .map(|activity| {
+                if problem.participants.is_empty() {
+                    return vec![0];
+                }
+                problem
+                    .participants
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, participant)| activity.involves(participant.id))
+                    .map(|(lane, _)| lane)
+                    .collect()
+            })
+            .collect();
+        let mut fixed_items: Vec<ScheduledItem> = problem
+            .fixed_indices
+            .iter()
+            .filter_map(|&act_idx| {
+                let start = problem.activities[act_idx].assigned_start?;
+                Some(ScheduledItem::new(&problem, act_idx, start))
+            })
+            .collect();
+        fixed_items.sort_unstable_by_key(ScheduledItem::order_key);
+        let latest_start = fixed_items
+            .iter()
+            .map(|item| item.start)
+            .fold(problem.total_slots, TimeSlot::max);
+
+        let mut forbidden_zones = ZoneIndex::default();
+        let mut activity_forbidden_zones = vec![ZoneIndex::default(); num_activities];
+        let mut cumulative_rules = Vec::<CumulativeRule>::new();
+        let mut empty_cumulative_totals = Vec::<Option<u32>>::new();
+        let mut precedence_edges = vec![Vec::<PrecedenceEdge>::new(); num_activities];
+        let mut exclusion_rules = Vec::<ExclusionRule>::new();
+        let mut busy_limit_rules = Vec::<BusyLimitRule>::new();
+        let mut workload_rules = Vec::<WorkloadRule>::new();
+        let mut free_time_rules = Vec::<FreeTimeRule>::new();
+        for constraint in &problem.global_constraints {
+            match constraint {
+                GlobalConstraint::Precedence {
+                    predecessor_id,
+                    successor_id,
+                    min_lag,
+                    max_lag,
+                    weight,
+                } => {
+                    if *predecessor_id >= num_activities || *successor_id >= num_activities {
+                        continue;
+                    }
+                    precedence_edges[*successor_id].push(PrecedenceEdge {
+                        predecessor_id: *predecessor_id,
+                        min_lag: *min_lag,
+                        max_lag: *max_lag,
+                        weight: *weight,
+                    });
                 }
-                for &(act_id, start_time, prob) in &self.problem.heatmap {
-                    if prob > 0.5 {
-                        if let Some(pos) = self
-                            .candidate_start_slots
-                            .iter()
-                            .position(|&s| s == start_time)
+                GlobalConstraint::Exclusion {
+                    first,
+                    second,
+                    window,
+                    weight,
+                } => exclusion_rules.push(ExclusionRule {
+                    first: *first,
+                    second: *second,
+                    window: *window,
+                    weight: *weight,
+                }),
+                GlobalConstraint::ContinuousBusyLimit {
+                    category_id,
+                    max_busy_slots,
+                    min_break_slots,
+                    weight,
+                } => busy_limit_rules.push(BusyLimitRule {
+                    category_id: *category_id,
+                    max_busy_slots: *max_busy_slots,
+                    min_break_slots: *min_break_slots,
+                    weight: *weight,
+                }),
+                GlobalConstraint::WorkloadLimit {
+                    scope,
+                    category_id,
+                    max_busy_slots,
+                    hard,
+                    penalty_weight,
+                } => workload_rules.push(WorkloadRule {
+                    scope: *scope,
+                    category_id: *category_id,
+                    max_busy_slots: *max_busy_slots,
+                    hard: *hard,
+                    penalty_weight: *penalty_weight,
+                }),
+                GlobalConstraint::FreeTimeTarget {
+                    scope,
+                    min_free_slots,
+                    weight,
+                } => {
+                    let mut available_by_bucket = vec![0u32; problem.bucket_count(*scope)];
+                    add_busy_by_bucket(
+                        &mut available_by_bucket,
+                        &problem,
+                        *scope,
+                        0,
+                        problem.total_slots,
+                    );
+                    for zone in &problem.global_constraints {
+                        if let GlobalConstraint::ForbiddenZone {
+                            start,
+                            end,
+                            activity_id: None,
+                        } = zone
                         {
-                            if act_id < self.no_activity_allele as usize {
-                                chromosome.genes[pos] = act_id as u16;
+                            let mut forbidden_by_bucket = vec![0u32; available_by_bucket.len()];
+                            add_busy_by_bucket(
+                                &mut forbidden_by_bucket,
+                                &problem,
+                                *scope,
+                                *start,
+                                *end,
+                            );
+                            for (available, forbidden) in
+                                available_by_bucket.iter_mut().zip(forbidden_by_bucket)
+                            {
+                                *available = available.saturating_sub(forbidden);
                             }
                         }
                     }
+                    free_time_rules.push(FreeTimeRule {
+                        scope: *scope,
+                        min_free_slots: *min_free_slots,
+                        weight: *weight,
+                        available_by_bucket,
+                    });
                 }
-                chromosome.reset_metadata(false);
-            }
-        }
-
-        let now = Instant::now();
-        self.call_for_population(
-            state.population_as_mut(),
-            genotype,
-            thread_local,
-            config.fitness_cache(),
-        );
-        state.add_duration(StrategyAction::Fitness, now.elapsed());
-    }
-
-    fn calculate_for_chromosome(
-        &mut self,
-        chromosome: &Chromosome<u16>,
-        _genotype: &Self::Genotype,
-    ) -> Option<FitnessValue> {
-        let genes = &chromosome.genes;
-
-        let mut score: f32 = 0.0;
-        let mut penalties: f32 = 0.0;
-
-        let num_activities = self.problem.activities.len();
-        let num_days = (self.problem.total_slots / 96) as usize + 1;
-        let num_weeks = (self.problem.total_slots / 672) as usize + 1;
-        let floating_count = self.problem.floating_indices.len();
-        let forbidden_zones: Vec<(u16, u16, Option<ActivityId>)> = self
-            .problem
-            .global_constraints
-            .iter()
-            .filter_map(|constraint| {
-                if let GlobalConstraint::ForbiddenZone {
+                GlobalConstraint::ForbiddenZone {
                     start,
                     end,
                     activity_id,
-                } = constraint
-                {
-                    Some((*start, *end, *activity_id))
-                } else {
-                    None
+                } => match activity_id {
+                    None => forbidden_zones.push(*start, *end),
+                    Some(activity_id) => {
+                        for (act_idx, activity) in problem.activities.iter().enumerate() {
+                            if activity.id == *activity_id {
+                                activity_forbidden_zones[act_idx].push(*start, *end);
+                            }
+                        }
+                    }
+                },
+                GlobalConstraint::CumulativeTime {
+                    activity_id,
+                    category_id,
+                    period_slots,
+                    min_duration,
+                    max_duration,
+                    deadline_end,
+                } => {
+                    let target = match (activity_id, category_id) {
+                        (Some(aid), Some(cid)) => {
+                            let activity_category_matches = problem
+                                .activities
+                                .get(*aid)
+                                .is_some_and(|activity| activity.category_id == *cid);
+                            if activity_category_matches {
+                                CumulativeTarget::Activity(*aid)
+                            } else {
+                                CumulativeTarget::Nothing
+                            }
+                        }
+                        (Some(aid), None) => CumulativeTarget::Activity(*aid),
+                        (None, Some(cid)) => CumulativeTarget::Category(*cid),
+                        (None, None) => CumulativeTarget::Global,
+                    };
+                    // Deadline totals always count; periodic buckets only once occupied.
+                    let period_slots = (*period_slots).max(1);
+                    let first_bucket = empty_cumulative_totals.len();
+                    if deadline_end.is_some() {
+                        empty_cumulative_totals.push(Some(0));
+                    } else if period_slots >= problem.total_slots {
+                        empty_cumulative_totals.push(None);
+                    } else {
+                        let bucket_count = (latest_start / period_slots) as usize + 1;
+                        empty_cumulative_totals.resize(first_bucket + bucket_count, None);
+                    }
+                    cumulative_rules.push(CumulativeRule {
+                        target,
+                        deadline_end: *deadline_end,
+                        period_slots,
+                        buckets: first_bucket..empty_cumulative_totals.len(),
+                        min_duration: *min_duration as u32,
+                        max_duration: *max_duration as u32,
+                    });
                 }
+            }
+        }
+        forbidden_zones.sort();
+        for zones in &mut activity_forbidden_zones {
+            zones.sort();
+        }
+        let cumulative_rules_by_activity = problem
+            .activities
+            .iter()
+            .map(|activity| {
+                (0..cumulative_rules.len())
+                    .filter(|&rule| cumulative_rules[rule].target.matches(activity))
+                    .collect()
             })
             .collect();
+        let num_days = problem.bucket_count(TimeScope::SameDay);
+        let num_weeks = problem.bucket_count(TimeScope::SameWeek);
+        let max_duration_slots = problem
+            .activities
+            .iter()
+            .map(|activity| activity.duration_slots)
+            .max()
+            .unwrap_or(0);
+        let max_buffer_before = problem
+            .activities
+            .iter()
+            .map(|activity| activity.buffer_before)
+            .max()
+            .unwrap_or(0);
 
-        // --- 1. PRE-PROCESSING TOTALS (O(N)) ---
-        // We calculate Total Counts first to handle Output bindings (Future = Total - Seen).
-        let mut total_day_counts = vec![vec![0u16; num_activities]; num_days];
-        let mut total_week_counts = vec![vec![0u16; num_activities]; num_weeks];
-        let mut total_month_counts = vec![0u16; num_activities];
-        let mut priority_occurrence_counts = vec![0u16; num_activities];
-
-        #[derive(Debug)]
-        struct ScheduledItem {
-            act_idx: usize,
-            start: u16,
-            end: u16,
-            day: usize,
-            week: usize,
-            weekday: usize,
+        Self {
+            problem,
+            candidate_start_slots,
+            no_activity_allele,
+            heatmap_table,
+            markov_table,
+            switch_cost_lookup,
+            batching_bonus_lookup,
+            travel_lookup,
+            objective: ObjectiveMode::default(),
+            resource_index,
+            activity_lanes,
+            precedence_edges,
+            exclusion_rules,
+            busy_limit_rules,
+            workload_rules,
+            free_time_rules,
+            max_duration_slots,
+            max_buffer_before,
+            forbidden_zones,
+            activity_forbidden_zones,
+            num_days,
+            num_weeks,
+            fixed_items,
+            cumulative_rules,
+            cumulative_rules_by_activity,
+            empty_cumulative_totals,
         }
+    }
+}
 
-        let total_items_cap =
-            self.problem.floating_indices.len() + self.problem.fixed_indices.len();
-        let mut schedule_items: Vec<ScheduledItem> = Vec::with_capacity(total_items_cap);
-        let mut no_activity_count: u32 = 0;
-
-        let mut register_item = |act_idx: usize, start_time: u16| {
-            let activity = &self.problem.activities[act_idx];
-            let end_time = start_time + activity.duration_slots;
-
-            if end_time > self.problem.total_slots {
-                penalties += Self::PENALTY_FORBIDDEN;
-            }
-
-            let day = (start_time / 96) as usize;
-            let week = (start_time / 672) as usize;
-            let weekday = day % 7;
+impl DiemFitness {
+    // Participants with their own timeline; one for a single-person problem.
+    fn lane_count(&self) -> usize {
+        self.problem.participants.len().max(1)
+    }
 
-            // Update Totals for Output Bindings
-            if day < num_days {
-                total_day_counts[day][activity.id] += 1;
-            }
-            if week < num_weeks {
-                total_week_counts[week][activity.id] += 1;
-            }
-            total_month_counts[activity.id] += 1;
+    /// Decodes genes into `(activity id, start slot)` placements of floating activities,
+    /// sorted by start.
+    pub fn decode(&self, genes: &[u16]) -> Vec<(ActivityId, TimeSlot)> {
+        let (placements, _) = self.gene_placements(genes);
+        let mut placements: Vec<(ActivityId, TimeSlot)> = placements
+            .into_iter()
+            .map(|(act_idx, start_time)| (self.problem.activities[act_idx].id, start_time))
+            .collect();
+        placements.sort_unstable_by_key(|&(_, start_time)| start_time);
+        placements
+    }
 
-            // Score: Priority (with diminishing returns) & Heatmap.
-            let occurrence_index = priority_occurrence_counts[activity.id] as i32;
-            let repeat_multiplier = Self::PRIORITY_REPEAT_DECAY.powi(occurrence_index);
-            score += activity.priority * Self::WEIGHT_PRIORITY * repeat_multiplier;
-            priority_occurrence_counts[activity.id] =
-                priority_occurrence_counts[activity.id].saturating_add(1);
-            if let Some(prob) = self.heatmap_lookup.get(&(activity.id, start_time)) {
-                score += prob * Self::WEIGHT_HEATMAP;
+    /// Encodes `(activity id, start slot)` placements as slot-indexed genes, using the
+    /// next free lane of each slot. Placements without a matching gene are dropped.
+    pub fn encode(&self, placements: &[(ActivityId, TimeSlot)]) -> Vec<u16> {
+        let mut genes = vec![self.no_activity_allele; self.candidate_start_slots.len()];
+        for &(activity_id, start_time) in placements {
+            let floating_choice = self
+                .problem
+                .floating_indices
+                .iter()
+                .position(|&act_idx| self.problem.activities[act_idx].id == activity_id);
+            let Some(floating_choice) = floating_choice else {
+                continue;
+            };
+            let first = self
+                .candidate_start_slots
+                .partition_point(|&s| s < start_time);
+            let free_lane = (first..self.candidate_start_slots.len())
+                .take_while(|&idx| self.candidate_start_slots[idx] == start_time)
+                .find(|&idx| genes[idx] == self.no_activity_allele);
+            if let Some(idx) = free_lane {
+                genes[idx] = floating_choice as u16;
             }
+        }
+        genes
+    }
 
-            schedule_items.push(ScheduledItem {
-                act_idx,
-                start: start_time,
-                end: end_time,
-                day,
-                week,
-                weekday,
-            });
-        };
-
-        // Process Genes (slot-indexed; activity choice encoded as allele).
+    // Slot-indexed genes as `(activity index, start slot)` pairs plus the sentinel count.
+    fn gene_placements(&self, genes: &[u16]) -> (Vec<(usize, TimeSlot)>, u32) {
+        let floating_count = self.problem.floating_indices.len();
+        let mut placements = Vec::new();
+        let mut no_activity_count: u32 = 0;
         for (gene_idx, &activity_choice) in genes.iter().enumerate() {
             if gene_idx >= self.candidate_start_slots.len() {
                 break;
@@ -237,30 +862,145 @@ impl Fitness for DiemFitness {
                 continue;
             }
             let start_time = self.candidate_start_slots[gene_idx];
-            let act_idx = self.problem.floating_indices[floating_choice];
-            register_item(act_idx, start_time);
+            placements.push((self.problem.floating_indices[floating_choice], start_time));
+        }
+        (placements, no_activity_count)
+    }
+
+    /// Scores a chromosome's genes, keeping rewards and penalties apart by kind.
+    pub fn evaluate(&self, genes: &[u16]) -> FitnessBreakdown {
+        let (placements, no_activity_count) = self.gene_placements(genes);
+        self.evaluate_placements(&placements, no_activity_count)
+    }
+
+    /// Scores floating `(activity index, start slot)` placements on top of the fixed
+    /// events, independent of the chromosome encoding. `no_activity_count` unused genes
+    /// earn the idle reward.
+    pub fn evaluate_placements(
+        &self,
+        placements: &[(usize, TimeSlot)],
+        no_activity_count: u32,
+    ) -> FitnessBreakdown {
+        let mut floating_items: Vec<ScheduledItem> = placements
+            .iter()
+            .map(|&(act_idx, start)| ScheduledItem::new(&self.problem, act_idx, start))
+            .collect();
+        // Decoded genes arrive in start order already.
+        if !floating_items.is_sorted_by_key(ScheduledItem::order_key) {
+            floating_items.sort_unstable_by_key(ScheduledItem::order_key);
         }
+        let schedule_items = self.merge_fixed(&floating_items);
+        self.score_items(&schedule_items, no_activity_count)
+    }
 
-        // Process Fixed
-        for &act_idx in &self.problem.fixed_indices {
-            if let Some(start) = self.problem.activities[act_idx].assigned_start {
-                register_item(act_idx, start);
+    // Merges floating items in sweep order with the precomputed fixed ones.
+    fn merge_fixed(&self, floating_items: &[ScheduledItem]) -> Vec<ScheduledItem> {
+        let mut merged = Vec::with_capacity(floating_items.len() + self.fixed_items.len());
+        let mut fixed = self.fixed_items.iter().peekable();
+        for item in floating_items {
+            while let Some(fixed_item) = fixed.next_if(|f| f.order_key() < item.order_key()) {
+                merged.push(*fixed_item);
             }
+            merged.push(*item);
         }
-        drop(register_item);
-        score += (no_activity_count as f32) * Self::REWARD_NO_ACTIVITY;
+        merged.extend(fixed);
+        merged
+    }
+
+    // Scores the whole schedule, fixed events included, given in sweep order.
+    fn score_items(
+        &self,
+        schedule_items: &[ScheduledItem],
+        no_activity_count: u32,
+    ) -> FitnessBreakdown {
+        SWEEP_SCRATCH
+            .with_borrow_mut(|scratch| self.sweep_items(schedule_items, no_activity_count, scratch))
+    }
+
+    fn sweep_items(
+        &self,
+        schedule_items: &[ScheduledItem],
+        no_activity_count: u32,
+        scratch: &mut SweepScratch,
+    ) -> FitnessBreakdown {
+        let mut breakdown = FitnessBreakdown::default();
+
+        let num_activities = self.problem.activities.len();
+        let num_days = self.num_days;
+        let num_weeks = self.num_weeks;
+        let lanes = self.lane_count();
+        let SweepScratch {
+            total_day_counts,
+            total_week_counts,
+            seen_occurrences,
+            busy_intervals,
+            cumulative_totals,
+            busy_by_bucket,
+            demand_events,
+        } = scratch;
+
+        // --- 1. PRE-PROCESSING TOTALS (O(N)) ---
+        // We calculate Total Counts first to handle Output bindings (Future = Total - Seen).
+        // Day and week counts are laid out as `bucket * num_activities + activity id`.
+        reset(total_day_counts, num_days * num_activities, 0);
+        reset(total_week_counts, num_weeks * num_activities, 0);
+        let mut total_month_counts = vec![0u16; num_activities];
+
+        for item in schedule_items {
+            let activity = &self.problem.activities[item.act_idx];
+            if item.end > self.problem.total_slots {
+                breakdown.add_hard(1, Self::PENALTY_FORBIDDEN);
+            }
+
+            // Update Totals for Output Bindings
+            if item.day < num_days {
+                total_day_counts[item.day * num_activities + activity.id] += 1;
+            }
+            if item.week < num_weeks {
+                total_week_counts[item.week * num_activities + activity.id] += 1;
+            }
 
-        // --- 2. SORT (O(N log N)) ---
-        schedule_items.sort_unstable_by_key(|k| k.start);
+            // Score: Priority (with diminishing returns) & Heatmap.
+            let occurrence_index = total_month_counts[activity.id] as i32;
+            let repeat_multiplier = Self::PRIORITY_REPEAT_DECAY.powi(occurrence_index);
+            breakdown.priority_reward +=
+                activity.priority * Self::WEIGHT_PRIORITY * repeat_multiplier;
+            total_month_counts[activity.id] = total_month_counts[activity.id].saturating_add(1);
+            breakdown.habit_reward +=
+                self.heatmap_table.get(activity.id, item.start) * Self::WEIGHT_HEATMAP;
+        }
+        breakdown.idle_reward += (no_activity_count as f32) * Self::REWARD_NO_ACTIVITY;
 
-        // --- 3. SEQUENTIAL SWEEP (O(N)) ---
+        // --- 2. SEQUENTIAL SWEEP (O(N)) ---
         let mut running_day_counts = vec![0u16; num_activities];
         let mut running_week_counts = vec![0u16; num_activities];
         let mut running_month_counts = vec![0u16; num_activities];
 
+        // Already-swept occurrences per activity. Each activity has a single duration, so
+        // ends arrive in non-decreasing order and stay sorted.
+        reset_each(seen_occurrences, num_activities);
+        let mut last_start_slots = vec![None::<TimeSlot>; num_activities];
+
         let mut prev_day = usize::MAX;
         let mut prev_week = usize::MAX;
-        let mut active_ends = Vec::with_capacity(schedule_items.len());
+        let mut active_items = Vec::<usize>::with_capacity(schedule_items.len());
+        let mut exclusion_counts = vec![
+            ExclusionCounts {
+                bucket: usize::MAX,
+                ..ExclusionCounts::default()
+            };
+            self.exclusion_rules.len()
+        ];
+        // Per-participant state is indexed by lane; see `activity_lanes`.
+        // Busy streak of each rule on each lane, at `rule * lanes + lane`.
+        let mut busy_streaks =
+            vec![None::<(TimeSlot, TimeSlot)>; self.busy_limit_rules.len() * lanes];
+        // Latest-ending located item so far per lane, as (end, location).
+        let mut last_located = vec![None::<(TimeSlot, LocationId)>; lanes];
+        // Latest item so far per lane, for Markov and category-switch effects.
+        let mut last_item = vec![None::<usize>; lanes];
+        // Occurrences and the travel booked right before them per lane; sorted after the sweep.
+        reset_each(busy_intervals, lanes);
         for i in 0..schedule_items.len() {
             let curr = &schedule_items[i];
             let activity = &self.problem.activities[curr.act_idx];
@@ -276,32 +1016,137 @@ impl Fitness for DiemFitness {
             }
 
             // --- A. Global Constraints (Forbidden Zones) ---
-            for (start, end, act_id) in &forbidden_zones {
-                if curr.start < *end && curr.end > *start {
-                    if act_id.is_none() || *act_id == Some(activity.id) {
-                        penalties += Self::PENALTY_FORBIDDEN;
-                    }
+            let forbidden = self.forbidden_zones.overlapping(curr.start, curr.end)
+                + self.activity_forbidden_zones[curr.act_idx].overlapping(curr.start, curr.end);
+            breakdown.add_hard(forbidden, Self::PENALTY_FORBIDDEN);
+
+            // --- B. Overlaps, Buffers, Breaks & Markov ---
+            // An earlier item stays relevant while its end plus buffers can reach a later start.
+            active_items.retain(|&j| {
+                let prev = &schedule_items[j];
+                let buffer_after = self.problem.activities[prev.act_idx].buffer_after;
+                prev.end as u32 + buffer_after as u32 + self.max_buffer_before as u32
+                    > curr.start as u32
+            });
+            for &j in &active_items {
+                let prev = &schedule_items[j];
+                let prev_activity = &self.problem.activities[prev.act_idx];
+                if !prev_activity.shares_participants(activity)
+                    || prev_activity.can_overlap(activity)
+                {
+                    continue;
+                }
+                if prev.end > curr.start {
+                    breakdown.add_hard(1, Self::PENALTY_OVERLAP);
+                } else {
+                    let required_gap =
+                        prev_activity.buffer_after as u32 + activity.buffer_before as u32;
+                    if ((curr.start - prev.end) as u32) < required_gap {
+                        breakdown.add_hard(1, Self::PENALTY_BUFFER);
+                    }
+                }
+            }
+
+            let curr_lanes = &self.activity_lanes[curr.act_idx];
+            if let Some(location) = activity.location_id {
+                // Each attending participant travels from where they were last.
+                let mut travel_violated = false;
+                for &lane in curr_lanes {
+                    let last = &mut last_located[lane];
+                    if let Some((prev_end, prev_location)) = *last {
+                        let travel = if prev_location == location {
+                            0
+                        } else {
+                            self.travel_lookup
+                                .get(&(prev_location, location))
+                                .copied()
+                                .unwrap_or(0)
+                        };
+                        if travel > 0 && prev_end <= curr.start {
+                            travel_violated |= curr.start - prev_end < travel;
+                            busy_intervals[lane].push(BusyInterval {
+                                start: curr.start.saturating_sub(travel),
+                                end: curr.start,
+                                category_id: None,
+                            });
+                        }
+                    }
+                    if last.is_none_or(|(prev_end, _)| curr.end >= prev_end) {
+                        *last = Some((curr.end, location));
+                    }
+                }
+                if travel_violated {
+                    breakdown.add_hard(1, Self::PENALTY_TRAVEL);
                 }
             }
 
-            // --- B. Overlaps & Markov ---
-            active_ends.retain(|&end| end > curr.start);
-            if !active_ends.is_empty() {
-                penalties += (active_ends.len() as f32) * Self::PENALTY_OVERLAP;
+            for (rule_idx, rule) in self.busy_limit_rules.iter().enumerate() {
+                if rule
+                    .category_id
+                    .is_some_and(|category_id| category_id != activity.category_id)
+                {
+                    continue;
+                }
+                for &lane in curr_lanes {
+                    let streak = &mut busy_streaks[rule_idx * lanes + lane];
+                    *streak = match *streak {
+                        Some((streak_start, streak_end))
+                            if (curr.start as u32)
+                                < streak_end as u32 + rule.min_break_slots as u32 =>
+                        {
+                            Some((streak_start, streak_end.max(curr.end)))
+                        }
+                        Some((streak_start, streak_end)) => {
+                            breakdown.constraint_penalty +=
+                                rule.excess_penalty(streak_start, streak_end);
+                            Some((curr.start, curr.end))
+                        }
+                        None => Some((curr.start, curr.end)),
+                    };
+                }
             }
 
-            if i > 0 {
-                let prev = &schedule_items[i - 1];
+            // Sequence effects only apply between activities of the same person: the
+            // latest earlier item on any of this item's lanes.
+            let sequence_prev = curr_lanes
+                .iter()
+                .filter_map(|&lane| last_item[lane])
+                .max()
+                .map(|j| &schedule_items[j]);
+            if let Some(prev) = sequence_prev {
                 if curr.start >= prev.end && curr.start - prev.end <= Self::MARKOV_GAP_TOLERANCE {
                     // Markov Reward
                     let prev_id = self.problem.activities[prev.act_idx].id;
                     let curr_id = activity.id;
-                    if let Some(prob) = self.markov_lookup.get(&(prev_id, curr_id)) {
-                        score += prob * Self::WEIGHT_MARKOV;
+                    breakdown.habit_reward +=
+                        self.markov_table.get(prev_id, curr_id) * Self::WEIGHT_MARKOV;
+                }
+                if curr.start >= prev.end
+                    && curr.start - prev.end <= Self::CONTEXT_SWITCH_GAP_TOLERANCE
+                {
+                    // Context Switch Cost / Batching Bonus
+                    let prev_category = self.problem.activities[prev.act_idx].category_id;
+                    let curr_category = activity.category_id;
+                    if prev_category == curr_category {
+                        if let Some(bonus) = self.batching_bonus_lookup.get(&curr_category) {
+                            breakdown.habit_reward += bonus;
+                        }
+                    } else if let Some(cost) =
+                        self.switch_cost_lookup.get(&(prev_category, curr_category))
+                    {
+                        breakdown.switch_penalty += cost;
                     }
                 }
             }
-            active_ends.push(curr.end);
+            active_items.push(i);
+            for &lane in curr_lanes {
+                last_item[lane] = Some(i);
+                busy_intervals[lane].push(BusyInterval {
+                    start: curr.start,
+                    end: curr.end,
+                    category_id: Some(activity.category_id),
+                });
+            }
 
             // --- C. INPUT BINDINGS (Strictly Before) ---
             for binding in &activity.input_bindings {
@@ -322,7 +1167,7 @@ impl Fitness for DiemFitness {
                     }
                 }
                 if !binding_met {
-                    penalties += binding.weight;
+                    breakdown.constraint_penalty += binding.weight;
                 }
             }
 
@@ -338,14 +1183,14 @@ impl Fitness for DiemFitness {
                         let total = match binding.time_scope {
                             TimeScope::SameDay => {
                                 if curr.day < num_days {
-                                    total_day_counts[curr.day][req_id]
+                                    total_day_counts[curr.day * num_activities + req_id]
                                 } else {
                                     0
                                 }
                             }
                             TimeScope::SameWeek => {
                                 if curr.week < num_weeks {
-                                    total_week_counts[curr.week][req_id]
+                                    total_week_counts[curr.week * num_activities + req_id]
                                 } else {
                                     0
                                 }
@@ -370,175 +1215,154 @@ impl Fitness for DiemFitness {
                     }
                 }
                 if !binding_met {
-                    penalties += binding.weight;
+                    breakdown.constraint_penalty += binding.weight;
                 }
             }
 
-            // --- UPDATE RUNNING COUNTS ---
-            running_day_counts[activity.id] += 1;
-            running_week_counts[activity.id] += 1;
-            running_month_counts[activity.id] += 1;
-        }
-
-        // --- 4. BUILD DEADLINE/PERIOD INDICES ---
-        // Prefix-style indices over event end-times for fast deadline queries.
-        let mut activity_month_end_slots = vec![Vec::<TimeSlot>::new(); num_activities];
-        let mut activity_day_end_slots = HashMap::<(usize, usize), Vec<TimeSlot>>::new();
-        let mut activity_week_end_slots = HashMap::<(usize, usize), Vec<TimeSlot>>::new();
-
-        let mut activity_duration_events = vec![Vec::<(TimeSlot, u16)>::new(); num_activities];
-        let mut category_duration_events = HashMap::<usize, Vec<(TimeSlot, u16)>>::new();
-        let mut global_duration_events =
-            Vec::<(TimeSlot, u16)>::with_capacity(schedule_items.len());
-
-        for item in &schedule_items {
-            let activity = &self.problem.activities[item.act_idx];
-            let activity_id = activity.id;
-            let end_slot = item.end.min(self.problem.total_slots);
-            let duration_slots = activity.duration_slots;
-
-            activity_month_end_slots[activity_id].push(end_slot);
-            activity_day_end_slots
-                .entry((activity_id, item.day))
-                .or_default()
-                .push(end_slot);
-            activity_week_end_slots
-                .entry((activity_id, item.week))
-                .or_default()
-                .push(end_slot);
-
-            activity_duration_events[activity_id].push((end_slot, duration_slots));
-            category_duration_events
-                .entry(activity.category_id)
-                .or_default()
-                .push((end_slot, duration_slots));
-            global_duration_events.push((end_slot, duration_slots));
-        }
-
-        for ends in &mut activity_month_end_slots {
-            ends.sort_unstable();
-        }
-        for ends in activity_day_end_slots.values_mut() {
-            ends.sort_unstable();
-        }
-        for ends in activity_week_end_slots.values_mut() {
-            ends.sort_unstable();
-        }
-
-        let activity_duration_prefix = activity_duration_events
-            .into_iter()
-            .map(DurationPrefixIndex::from_events)
-            .collect::<Vec<_>>();
-        let mut category_duration_prefix = HashMap::<usize, DurationPrefixIndex>::new();
-        for (category_id, events) in category_duration_events {
-            category_duration_prefix.insert(category_id, DurationPrefixIndex::from_events(events));
-        }
-        let global_duration_prefix = DurationPrefixIndex::from_events(global_duration_events);
-
-        // Precompute bucket totals for periodic (non-deadline) cumulative constraints.
-        let mut activity_periods = HashMap::<usize, Vec<u16>>::new();
-        let mut category_periods = HashMap::<usize, Vec<u16>>::new();
-        let mut global_periods = Vec::<u16>::new();
-
-        for constraint in &self.problem.global_constraints {
-            if let GlobalConstraint::CumulativeTime {
-                activity_id,
-                category_id,
-                period_slots,
-                deadline_end,
-                ..
-            } = constraint
-            {
-                if deadline_end.is_some() {
+            // --- E. EXCLUSIONS (Negative Bindings against Earlier Occurrences) ---
+            for (rule, counts) in self.exclusion_rules.iter().zip(&mut exclusion_counts) {
+                let curr_is_first = rule.first.matches(activity);
+                let curr_is_second = rule.second.matches(activity);
+                if !curr_is_first && !curr_is_second {
                     continue;
                 }
-                match (activity_id, category_id) {
-                    (Some(aid), _) => {
-                        let periods = activity_periods.entry(*aid).or_default();
-                        if !periods.contains(period_slots) {
-                            periods.push(*period_slots);
+
+                let conflicts = match rule.window {
+                    ExclusionWindow::Scope(scope) => {
+                        let bucket = match scope {
+                            TimeScope::SameDay => curr.day,
+                            TimeScope::SameWeek => curr.week,
+                            TimeScope::SameMonth => 0,
+                        };
+                        // Items are sorted by start, so buckets are contiguous.
+                        if counts.bucket != bucket {
+                            *counts = ExclusionCounts {
+                                bucket,
+                                ..ExclusionCounts::default()
+                            };
                         }
-                    }
-                    (None, Some(cid)) => {
-                        let periods = category_periods.entry(*cid).or_default();
-                        if !periods.contains(period_slots) {
-                            periods.push(*period_slots);
+                        let mut conflicts = 0u32;
+                        if curr_is_second {
+                            conflicts += counts.first;
+                        }
+                        if curr_is_first {
+                            conflicts += counts.second;
                         }
+                        // An earlier occurrence matching both selectors is one pair.
+                        if curr_is_first && curr_is_second {
+                            conflicts -= counts.both;
+                        }
+                        counts.first += curr_is_first as u32;
+                        counts.second += curr_is_second as u32;
+                        counts.both += (curr_is_first && curr_is_second) as u32;
+                        conflicts
                     }
-                    (None, None) => {
-                        if !global_periods.contains(period_slots) {
-                            global_periods.push(*period_slots);
+                    ExclusionWindow::Lag { min_lag, max_lag } => {
+                        let mut conflicts = 0u32;
+                        for prev in schedule_items[..i].iter().rev() {
+                            let reach = prev.start as u32 + self.max_duration_slots as u32;
+                            if reach + (max_lag as u32) < curr.start as u32 {
+                                break;
+                            }
+                            if curr_is_second
+                                && prev.end <= curr.start
+                                && (min_lag..=max_lag).contains(&(curr.start - prev.end))
+                                && rule.first.matches(&self.problem.activities[prev.act_idx])
+                            {
+                                conflicts += 1;
+                            }
                         }
+                        conflicts
                     }
-                }
+                };
+                breakdown.constraint_penalty += conflicts as f32 * rule.weight;
             }
-        }
 
-        let mut activity_period_totals = HashMap::<(usize, u16), HashMap<usize, u32>>::new();
-        for (activity_id, periods) in &activity_periods {
-            for period_slots in periods {
-                activity_period_totals.insert((*activity_id, *period_slots), HashMap::new());
-            }
-        }
-        let mut category_period_totals = HashMap::<(usize, u16), HashMap<usize, u32>>::new();
-        for (category_id, periods) in &category_periods {
-            for period_slots in periods {
-                category_period_totals.insert((*category_id, *period_slots), HashMap::new());
+            // --- F. PRECEDENCE (Min/Max Lag after a Predecessor Ends) ---
+            for edge in &self.precedence_edges[activity.id] {
+                let satisfied = curr
+                    .start
+                    .checked_sub(edge.min_lag)
+                    .is_some_and(|latest_end| {
+                        let seen = &seen_occurrences[edge.predecessor_id];
+                        let idx = seen.partition_point(|occurrence| occurrence.end <= latest_end);
+                        idx > 0
+                            && edge
+                                .max_lag
+                                .is_none_or(|max_lag| curr.start - seen[idx - 1].end <= max_lag)
+                    });
+                if !satisfied {
+                    breakdown.constraint_penalty += edge.weight;
+                }
             }
-        }
-        let mut global_period_totals = HashMap::<u16, HashMap<usize, u32>>::new();
-        for period_slots in &global_periods {
-            global_period_totals.insert(*period_slots, HashMap::new());
-        }
 
-        for item in &schedule_items {
-            let activity = &self.problem.activities[item.act_idx];
-            let duration = activity.duration_slots as u32;
-            let start = item.start;
-
-            if let Some(periods) = activity_periods.get(&activity.id) {
-                for period_slots in periods {
-                    let bucket = if *period_slots >= self.problem.total_slots {
-                        0
-                    } else {
-                        (start / *period_slots) as usize
-                    };
-                    if let Some(bucket_totals) =
-                        activity_period_totals.get_mut(&(activity.id, *period_slots))
-                    {
-                        *bucket_totals.entry(bucket).or_insert(0) += duration;
+            // --- G. OCCURRENCE SPACING (Gap since the Previous Occurrence) ---
+            if let Some(prev_start) = last_start_slots[activity.id] {
+                let gap_index = running_month_counts[activity.id] as usize - 1;
+                let spacing = activity
+                    .occurrence_spacing
+                    .get(gap_index)
+                    .or(activity.occurrence_spacing.last());
+                if let Some(spacing) = spacing {
+                    let gap = curr.start - prev_start;
+                    if gap < spacing.min_gap || spacing.max_gap.is_some_and(|max| gap > max) {
+                        breakdown.constraint_penalty += spacing.weight;
                     }
                 }
             }
+            last_start_slots[activity.id] = Some(curr.start);
 
-            if let Some(periods) = category_periods.get(&activity.category_id) {
-                for period_slots in periods {
-                    let bucket = if *period_slots >= self.problem.total_slots {
-                        0
-                    } else {
-                        (start / *period_slots) as usize
-                    };
-                    if let Some(bucket_totals) =
-                        category_period_totals.get_mut(&(activity.category_id, *period_slots))
-                    {
-                        *bucket_totals.entry(bucket).or_insert(0) += duration;
-                    }
+            // --- UPDATE RUNNING COUNTS ---
+            running_day_counts[activity.id] += 1;
+            running_week_counts[activity.id] += 1;
+            running_month_counts[activity.id] += 1;
+            seen_occurrences[activity.id].push(SeenOccurrence {
+                day: curr.day,
+                week: curr.week,
+                end: curr.end,
+            });
+        }
+
+        for (rule_idx, rule) in self.busy_limit_rules.iter().enumerate() {
+            for streak in &busy_streaks[rule_idx * lanes..(rule_idx + 1) * lanes] {
+                if let Some((streak_start, streak_end)) = *streak {
+                    breakdown.constraint_penalty += rule.excess_penalty(streak_start, streak_end);
                 }
             }
+        }
 
-            for period_slots in &global_periods {
-                let bucket = if *period_slots >= self.problem.total_slots {
-                    0
-                } else {
-                    (start / *period_slots) as usize
+        for lane_intervals in busy_intervals.iter_mut() {
+            // Travel starts before its occurrence, so only travel can be out of order.
+            lane_intervals.sort_unstable_by_key(|interval| interval.start);
+            let mut busy_slots: u32 = 0;
+            for_each_occupied(lane_intervals, |start, end| {
+                busy_slots += end.min(self.problem.total_slots).saturating_sub(start) as u32;
+            });
+            breakdown.free_slots += (self.problem.total_slots as u32).saturating_sub(busy_slots);
+        }
+
+        // Cumulative totals: slots before each deadline, and slots per periodic bucket
+        // for buckets that hold at least one occurrence.
+        cumulative_totals.clone_from(&self.empty_cumulative_totals);
+        for item in schedule_items {
+            let duration = self.problem.activities[item.act_idx].duration_slots as u32;
+            for &rule_idx in &self.cumulative_rules_by_activity[item.act_idx] {
+                let rule = &self.cumulative_rules[rule_idx];
+                let bucket = match rule.deadline_end {
+                    Some(deadline) if item.end.min(self.problem.total_slots) > deadline => {
+                        continue;
+                    }
+                    Some(_) => 0,
+                    None if rule.period_slots >= self.problem.total_slots => 0,
+                    None => (item.start / rule.period_slots) as usize,
                 };
-                if let Some(bucket_totals) = global_period_totals.get_mut(period_slots) {
-                    *bucket_totals.entry(bucket).or_insert(0) += duration;
+                if let Some(total) = cumulative_totals[rule.buckets.clone()].get_mut(bucket) {
+                    *total = Some(total.unwrap_or(0) + duration);
                 }
             }
         }
 
-        // --- 5. SOFT FREQUENCY TARGETS (Reward + Overshoot Penalty) ---
+        // --- 3. SOFT FREQUENCY TARGETS (Reward + Overshoot Penalty) ---
         // If an activity exceeds its soft learned history cap, apply a strict 200 pt penalty per excess.
         // This stops flooding from priority rewards since 200 > 20.
         // If the activity HAS a user constraint (e.g., Min 5/week), the user
@@ -556,9 +1380,9 @@ impl Fitness for DiemFitness {
 
                 match target.scope {
                     TimeScope::SameDay => {
-                        for d in 0..num_days {
-                            let actual = total_day_counts[d][activity.id];
-                            score += reward_for_count(actual);
+                        for day_counts in total_day_counts.chunks_exact(num_activities) {
+                            let actual = day_counts[activity.id];
+                            breakdown.frequency_reward += reward_for_count(actual);
                             let cap = if target.target_count == 0 {
                                 1
                             } else {
@@ -566,15 +1390,15 @@ impl Fitness for DiemFitness {
                             };
                             if actual > cap {
                                 let excess = (actual - cap) as f32;
-                                penalties +=
+                                breakdown.frequency_penalty +=
                                     target.weight * Self::FREQUENCY_OVERSHOOT_MULTIPLIER * excess;
                             }
                         }
                     }
                     TimeScope::SameWeek => {
-                        for w in 0..num_weeks {
-                            let actual = total_week_counts[w][activity.id];
-                            score += reward_for_count(actual);
+                        for week_counts in total_week_counts.chunks_exact(num_activities) {
+                            let actual = week_counts[activity.id];
+                            breakdown.frequency_reward += reward_for_count(actual);
                             let cap = if target.target_count == 0 {
                                 1
                             } else {
@@ -582,14 +1406,14 @@ impl Fitness for DiemFitness {
                             };
                             if actual > cap {
                                 let excess = (actual - cap) as f32;
-                                penalties +=
+                                breakdown.frequency_penalty +=
                                     target.weight * Self::FREQUENCY_OVERSHOOT_MULTIPLIER * excess;
                             }
                         }
                     }
                     TimeScope::SameMonth => {
                         let actual = total_month_counts[activity.id];
-                        score += reward_for_count(actual);
+                        breakdown.frequency_reward += reward_for_count(actual);
                         let cap = if target.target_count == 0 {
                             1
                         } else {
@@ -597,7 +1421,7 @@ impl Fitness for DiemFitness {
                         };
                         if actual > cap {
                             let excess = (actual - cap) as f32;
-                            penalties +=
+                            breakdown.frequency_penalty +=
                                 target.weight * Self::FREQUENCY_OVERSHOOT_MULTIPLIER * excess;
                         }
                     }
@@ -605,63 +1429,75 @@ impl Fitness for DiemFitness {
             }
         }
 
-        // --- 6. USER FREQUENCY CONSTRAINTS (RELATIVELY HARD PENALTY) ---
+        // --- 4. USER FREQUENCY CONSTRAINTS (RELATIVELY HARD PENALTY) ---
         for activity in &self.problem.activities {
             for constraint in &activity.user_frequency_constraints {
                 match constraint.scope {
                     TimeScope::SameDay => {
-                        for d in 0..num_days {
-                            let bucket_start = (d as u16) * 96;
+                        for (d, day_counts) in
+                            total_day_counts.chunks_exact(num_activities).enumerate()
+                        {
+                            let bucket_start = self.problem.bucket_start(TimeScope::SameDay, d);
                             let actual = if let Some(deadline_end) = constraint.deadline_end {
                                 if bucket_start > deadline_end {
                                     continue;
                                 }
-                                activity_day_end_slots
-                                    .get(&(activity.id, d))
-                                    .map_or(0, |ends| count_ends_leq(ends, deadline_end))
+                                count_ends_leq(
+                                    &seen_occurrences[activity.id],
+                                    |occurrence| occurrence.day,
+                                    d,
+                                    deadline_end,
+                                    self.problem.total_slots,
+                                )
                             } else {
-                                total_day_counts[d][activity.id] as u32
+                                day_counts[activity.id] as u32
                             };
                             if let Some(min_count) = constraint.min_count {
                                 let min_count = min_count as u32;
                                 if actual < min_count {
-                                    penalties +=
+                                    breakdown.constraint_penalty +=
                                         (min_count - actual) as f32 * constraint.penalty_weight;
                                 }
                             }
                             if let Some(max_count) = constraint.max_count {
                                 let max_count = max_count as u32;
                                 if actual > max_count {
-                                    penalties +=
+                                    breakdown.constraint_penalty +=
                                         (actual - max_count) as f32 * constraint.penalty_weight;
                                 }
                             }
                         }
                     }
                     TimeScope::SameWeek => {
-                        for w in 0..num_weeks {
-                            let bucket_start = (w as u16) * 672;
+                        for (w, week_counts) in
+                            total_week_counts.chunks_exact(num_activities).enumerate()
+                        {
+                            let bucket_start = self.problem.bucket_start(TimeScope::SameWeek, w);
                             let actual = if let Some(deadline_end) = constraint.deadline_end {
                                 if bucket_start > deadline_end {
                                     continue;
                                 }
-                                activity_week_end_slots
-                                    .get(&(activity.id, w))
-                                    .map_or(0, |ends| count_ends_leq(ends, deadline_end))
+                                count_ends_leq(
+                                    &seen_occurrences[activity.id],
+                                    |occurrence| occurrence.week,
+                                    w,
+                                    deadline_end,
+                                    self.problem.total_slots,
+                                )
                             } else {
-                                total_week_counts[w][activity.id] as u32
+                                week_counts[activity.id] as u32
                             };
                             if let Some(min_count) = constraint.min_count {
                                 let min_count = min_count as u32;
                                 if actual < min_count {
-                                    penalties +=
+                                    breakdown.constraint_penalty +=
                                         (min_count - actual) as f32 * constraint.penalty_weight;
                                 }
                             }
                             if let Some(max_count) = constraint.max_count {
                                 let max_count = max_count as u32;
                                 if actual > max_count {
-                                    penalties +=
+                                    breakdown.constraint_penalty +=
                                         (actual - max_count) as f32 * constraint.penalty_weight;
                                 }
                             }
@@ -669,21 +1505,27 @@ impl Fitness for DiemFitness {
                     }
                     TimeScope::SameMonth => {
                         let actual = if let Some(deadline_end) = constraint.deadline_end {
-                            count_ends_leq(&activity_month_end_slots[activity.id], deadline_end)
+                            count_ends_leq(
+                                &seen_occurrences[activity.id],
+                                |_| 0,
+                                0,
+                                deadline_end,
+                                self.problem.total_slots,
+                            )
                         } else {
                             total_month_counts[activity.id] as u32
                         };
                         if let Some(min_count) = constraint.min_count {
                             let min_count = min_count as u32;
                             if actual < min_count {
-                                penalties +=
+                                breakdown.constraint_penalty +=
                                     (min_count - actual) as f32 * constraint.penalty_weight;
                             }
                         }
                         if let Some(max_count) = constraint.max_count {
                             let max_count = max_count as u32;
                             if actual > max_count {
-                                penalties +=
+                                breakdown.constraint_penalty +=
                                     (actual - max_count) as f32 * constraint.penalty_weight;
                             }
                         }
@@ -692,89 +1534,293 @@ impl Fitness for DiemFitness {
             }
         }
 
-        // --- 7. CHECK CUMULATIVE TIME CONSTRAINTS ---
-        // Deadline mode uses end-time prefix sums; periodic mode uses prebuilt bucket totals.
-        for constraint in &self.problem.global_constraints {
-            if let GlobalConstraint::CumulativeTime {
-                activity_id,
-                category_id,
-                period_slots,
-                min_duration,
-                max_duration,
-                deadline_end,
-            } = constraint
-            {
-                if let Some(deadline) = deadline_end {
-                    let total_before_deadline: u32 = match (activity_id, category_id) {
-                        (Some(aid), Some(cid)) => {
-                            let activity_category_matches = self
-                                .problem
-                                .activities
-                                .get(*aid)
-                                .is_some_and(|activity| activity.category_id == *cid);
-                            if activity_category_matches {
-                                activity_duration_prefix
-                                    .get(*aid)
-                                    .map_or(0, |index| index.sum_leq(*deadline))
-                            } else {
-                                0
-                            }
-                        }
-                        (Some(aid), None) => activity_duration_prefix
-                            .get(*aid)
-                            .map_or(0, |index| index.sum_leq(*deadline)),
-                        (None, Some(cid)) => category_duration_prefix
-                            .get(cid)
-                            .map_or(0, |index| index.sum_leq(*deadline)),
-                        (None, None) => global_duration_prefix.sum_leq(*deadline),
-                    };
+        // --- 5. CHECK CUMULATIVE TIME CONSTRAINTS ---
+        // Deadline rules have a single total; periodic rules one per occupied bucket.
+        for rule in &self.cumulative_rules {
+            for &total in cumulative_totals[rule.buckets.clone()].iter().flatten() {
+                if total < rule.min_duration {
+                    breakdown.add_hard_excess(rule.min_duration - total, Self::PENALTY_CUMULATIVE);
+                }
+                if total > rule.max_duration {
+                    breakdown.add_hard_excess(total - rule.max_duration, Self::PENALTY_CUMULATIVE);
+                }
+            }
+        }
 
-                    if total_before_deadline < (*min_duration as u32) {
-                        penalties += Self::PENALTY_CUMULATIVE
-                            * ((*min_duration as u32 - total_before_deadline) as f32);
-                    }
-                    if total_before_deadline > (*max_duration as u32) {
-                        penalties += Self::PENALTY_CUMULATIVE
-                            * ((total_before_deadline - *max_duration as u32) as f32);
+        // --- 6. WORKLOAD LIMITS & FREE TIME (Calendar Buckets, per Participant) ---
+        // Category limits count that category's occurrences; overall limits add travel.
+        for rule in &self.workload_rules {
+            for lane_intervals in busy_intervals.iter() {
+                reset(busy_by_bucket, self.problem.bucket_count(rule.scope), 0);
+                for_each_busy_union(
+                    lane_intervals,
+                    |interval| {
+                        rule.category_id
+                            .is_none_or(|category_id| interval.category_id == Some(category_id))
+                    },
+                    |start, end| {
+                        add_busy_by_bucket(busy_by_bucket, &self.problem, rule.scope, start, end)
+                    },
+                );
+                for &busy in busy_by_bucket.iter() {
+                    let excess = busy.saturating_sub(rule.max_busy_slots as u32);
+                    if rule.hard {
+                        breakdown.add_hard_excess(excess, Self::PENALTY_WORKLOAD_HARD);
+                    } else {
+                        breakdown.constraint_penalty += excess as f32 * rule.penalty_weight;
                     }
-                    continue;
                 }
+            }
+        }
 
-                let bucket_totals = match (activity_id, category_id) {
-                    (Some(aid), Some(cid)) => {
-                        let activity_category_matches = self
-                            .problem
-                            .activities
-                            .get(*aid)
-                            .is_some_and(|activity| activity.category_id == *cid);
-                        if activity_category_matches {
-                            activity_period_totals.get(&(*aid, *period_slots))
-                        } else {
-                            None
-                        }
-                    }
-                    (Some(aid), None) => activity_period_totals.get(&(*aid, *period_slots)),
-                    (None, Some(cid)) => category_period_totals.get(&(*cid, *period_slots)),
-                    (None, None) => global_period_totals.get(period_slots),
-                };
+        for rule in &self.free_time_rules {
+            for lane_intervals in busy_intervals.iter() {
+                reset(busy_by_bucket, rule.available_by_bucket.len(), 0);
+                for_each_occupied(lane_intervals, |start, end| {
+                    add_busy_by_bucket(busy_by_bucket, &self.problem, rule.scope, start, end)
+                });
+                for (available, &busy) in rule.available_by_bucket.iter().zip(busy_by_bucket.iter())
+                {
+                    // Partial buckets at the horizon edges cannot offer the full target.
+                    let required = (rule.min_free_slots as u32).min(*available);
+                    let free = available.saturating_sub(busy);
+                    breakdown.constraint_penalty +=
+                        required.saturating_sub(free) as f32 * rule.weight;
+                }
+            }
+        }
 
-                if let Some(bucket_totals) = bucket_totals {
-                    for total in bucket_totals.values() {
-                        if *total < (*min_duration as u32) {
-                            penalties +=
-                                Self::PENALTY_CUMULATIVE * ((*min_duration as u32 - *total) as f32);
-                        }
-                        if *total > (*max_duration as u32) {
-                            penalties +=
-                                Self::PENALTY_CUMULATIVE * ((*total - *max_duration as u32) as f32);
-                        }
+        // --- 7. SHARED RESOURCE CAPACITY (Demand Sweep) ---
+        if !self.resource_index.is_empty() {
+            reset_each(demand_events, self.resource_index.len());
+            for item in schedule_items {
+                let activity = &self.problem.activities[item.act_idx];
+                for requirement in &activity.resource_requirements {
+                    if let Some(&index) = self.resource_index.get(&requirement.resource_id) {
+                        let amount = requirement.amount as i32;
+                        demand_events[index].push((item.start, amount));
+                        demand_events[index].push((item.end, -amount));
+                    }
+                }
+            }
+            for (resource, events) in self.problem.resources.iter().zip(demand_events.iter_mut()) {
+                // Releases sort before acquisitions at the same slot.
+                events.sort_unstable();
+                let capacity = resource.capacity as i32;
+                let mut demand = 0i32;
+                let mut prev_slot = 0u16;
+                // Over-capacity slot units of the current stretch; each stretch is one violation.
+                let mut stretch = 0u32;
+                for &(slot, delta) in events.iter() {
+                    let excess = (demand - capacity).max(0) as u32;
+                    if excess > 0 {
+                        stretch += excess * (slot - prev_slot) as u32;
+                    } else if slot > prev_slot {
+                        breakdown.add_hard_excess(stretch, Self::PENALTY_RESOURCE);
+                        stretch = 0;
                     }
+                    demand += delta;
+                    prev_slot = slot;
                 }
+                breakdown.add_hard_excess(stretch, Self::PENALTY_RESOURCE);
+            }
+        }
+
+        breakdown
+    }
+}
+
+/// A local change to floating placements; indices are positions in
+/// `IncrementalSchedule::placements`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlacementMove {
+    Remove(usize),
+    /// Moves a placement to a new start slot.
+    Shift(usize, TimeSlot),
+    /// Exchanges the start slots of two placements.
+    Swap(usize, usize),
+    /// Adds an `(activity index, start slot)` placement.
+    Insert(usize, TimeSlot),
+}
+
+// At most two placements leave and two arrive in one move.
+type MoveChanges = [Option<(usize, TimeSlot)>; 2];
+
+/// Floating placements kept merged with the fixed events in sweep order. A local move
+/// patches the items it touches instead of decoding, merging and sorting the schedule
+/// again, but is still scored by a full sweep: no per-rule state is updated by delta.
+#[derive(Debug, Clone)]
+pub struct IncrementalSchedule {
+    placements: Vec<(usize, TimeSlot)>,
+    items: Vec<ScheduledItem>,
+    no_activity_count: u32,
+}
+
+impl IncrementalSchedule {
+    /// Floating `(activity index, start slot)` placements, in the order moves refer to.
+    pub fn placements(&self) -> &[(usize, TimeSlot)] {
+        &self.placements
+    }
+
+    fn changes(&self, placement_move: PlacementMove) -> (MoveChanges, MoveChanges) {
+        match placement_move {
+            PlacementMove::Remove(i) => ([Some(self.placements[i]), None], [None, None]),
+            PlacementMove::Shift(i, start) => {
+                let (act_idx, _) = self.placements[i];
+                (
+                    [Some(self.placements[i]), None],
+                    [Some((act_idx, start)), None],
+                )
+            }
+            PlacementMove::Swap(i, j) => {
+                let (a, a_start) = self.placements[i];
+                let (b, b_start) = self.placements[j];
+                (
+                    [Some((a, a_start)), Some((b, b_start))],
+                    [Some((a, b_start)), Some((b, a_start))],
+                )
+            }
+            PlacementMove::Insert(act_idx, start) => ([None, None], [Some((act_idx, start)), None]),
+        }
+    }
+
+    fn patch(&mut self, problem: &Problem, removed: &MoveChanges, added: &MoveChanges) {
+        for &(act_idx, start) in removed.iter().flatten() {
+            let key = (start, act_idx);
+            let idx = self.items.partition_point(|item| item.order_key() < key);
+            if self
+                .items
+                .get(idx)
+                .is_some_and(|item| item.order_key() == key)
+            {
+                self.items.remove(idx);
+            }
+        }
+        for &(act_idx, start) in added.iter().flatten() {
+            let item = ScheduledItem::new(problem, act_idx, start);
+            let idx = self
+                .items
+                .partition_point(|other| other.order_key() <= item.order_key());
+            self.items.insert(idx, item);
+        }
+    }
+}
+
+impl DiemFitness {
+    /// Prepares `placements` for move evaluation; `no_activity_count` is kept in step
+    /// with inserts and removals.
+    pub fn incremental_schedule(
+        &self,
+        placements: &[(usize, TimeSlot)],
+        no_activity_count: u32,
+    ) -> IncrementalSchedule {
+        let mut floating_items: Vec<ScheduledItem> = placements
+            .iter()
+            .map(|&(act_idx, start)| ScheduledItem::new(&self.problem, act_idx, start))
+            .collect();
+        floating_items.sort_unstable_by_key(ScheduledItem::order_key);
+        IncrementalSchedule {
+            placements: placements.to_vec(),
+            items: self.merge_fixed(&floating_items),
+            no_activity_count,
+        }
+    }
+
+    /// Scores the schedule as it stands; matches `evaluate_placements` on its placements.
+    pub fn evaluate_schedule(&self, schedule: &IncrementalSchedule) -> FitnessBreakdown {
+        self.score_items(&schedule.items, schedule.no_activity_count)
+    }
+
+    /// Scores `schedule` with `placement_move` applied, leaving the schedule as it was.
+    pub fn evaluate_move(
+        &self,
+        schedule: &mut IncrementalSchedule,
+        placement_move: PlacementMove,
+    ) -> FitnessBreakdown {
+        let (removed, added) = schedule.changes(placement_move);
+        let idle = Self::idle_after(schedule.no_activity_count, placement_move);
+        schedule.patch(&self.problem, &removed, &added);
+        let breakdown = self.score_items(&schedule.items, idle);
+        schedule.patch(&self.problem, &added, &removed);
+        breakdown
+    }
+
+    /// Applies `placement_move` to `schedule`.
+    pub fn apply_move(&self, schedule: &mut IncrementalSchedule, placement_move: PlacementMove) {
+        let (removed, added) = schedule.changes(placement_move);
+        schedule.patch(&self.problem, &removed, &added);
+        schedule.no_activity_count = Self::idle_after(schedule.no_activity_count, placement_move);
+        match placement_move {
+            PlacementMove::Remove(i) => {
+                schedule.placements.remove(i);
+            }
+            PlacementMove::Shift(i, start) => schedule.placements[i].1 = start,
+            PlacementMove::Swap(i, j) => {
+                let a_start = schedule.placements[i].1;
+                schedule.placements[i].1 = schedule.placements[j].1;
+                schedule.placements[j].1 = a_start;
+            }
+            PlacementMove::Insert(act_idx, start) => schedule.placements.push((act_idx, start)),
+        }
+    }
+
+    fn idle_after(no_activity_count: u32, placement_move: PlacementMove) -> u32 {
+        match placement_move {
+            PlacementMove::Remove(_) => no_activity_count.saturating_add(1),
+            PlacementMove::Insert(..) => no_activity_count.saturating_sub(1),
+            PlacementMove::Shift(..) | PlacementMove::Swap(..) => no_activity_count,
+        }
+    }
+}
+
+impl Fitness for DiemFitness {
+    type Genotype = RangeGenotype<u16>;
+
+    fn call_for_state_population<S: StrategyState<Self::Genotype>, C: StrategyConfig>(
+        &mut self,
+        genotype: &Self::Genotype,
+        state: &mut S,
+        config: &C,
+        thread_local: Option<&ThreadLocal<RefCell<Self>>>,
+    ) {
+        if state.current_generation() == 0 {
+            let population = state.population_as_mut();
+            if let Some(chromosome) = population.chromosomes.first_mut() {
+                chromosome.genes = self.seed_genes();
+                chromosome.reset_metadata(false);
             }
         }
 
-        let final_score = score - penalties;
-        Some(final_score as isize)
+        let now = Instant::now();
+        self.call_for_population(
+            state.population_as_mut(),
+            genotype,
+            thread_local,
+            config.fitness_cache(),
+        );
+        state.add_duration(StrategyAction::Fitness, now.elapsed());
+    }
+
+    fn calculate_for_chromosome(
+        &mut self,
+        chromosome: &Chromosome<u16>,
+        _genotype: &Self::Genotype,
+    ) -> Option<FitnessValue> {
+        Some(self.objective.score(&self.evaluate(&chromosome.genes)))
+    }
+}
+
+impl SeededFitness for DiemFitness {
+    fn seed_genes(&self) -> Vec<u16> {
+        self.encode(&greedy_schedule(&self.problem))
+    }
+
+    fn floating_count(&self) -> usize {
+        self.problem.floating_indices.len()
+    }
+
+    fn schedule_layout(&self) -> Option<ScheduleLayout> {
+        Some(ScheduleLayout::new(self))
     }
 }
 
@@ -783,12 +1829,12 @@ mod tests {
     use super::*;
     use crate::solver::candidate_slots::build_candidate_start_slots;
     use crate::solver::types::{
-        Activity, ActivityType, Binding, FrequencyTarget, GlobalConstraint, Problem, TimeScope,
-        UserFrequencyConstraint,
+        Activity, ActivitySelector, ActivityType, Binding, ExclusionWindow, FrequencyTarget,
+        GlobalConstraint, OccurrenceSpacing, Participant, Problem, Resource, ResourceRequirement,
+        TimeScope, UserFrequencyConstraint,
     };
     use genetic_algorithm::chromosome::Chromosome;
     use genetic_algorithm::genotype::{Genotype, RangeGenotype};
-    use std::collections::HashMap;
 
     fn base_activity(id: usize) -> Activity {
         Activity {
@@ -802,6 +1848,14 @@ mod tests {
             output_bindings: vec![],
             frequency_targets: Vec::<FrequencyTarget>::new(),
             user_frequency_constraints: Vec::<UserFrequencyConstraint>::new(),
+            occurrence_spacing: vec![],
+            buffer_before: 0,
+            buffer_after: 0,
+            location_id: None,
+            resource_requirements: vec![],
+            attention_free: false,
+            overlap_compatible_categories: vec![],
+            participant_ids: vec![],
         }
     }
 
@@ -825,13 +1879,10 @@ mod tests {
     ) -> Chromosome<u16> {
         let sentinel = no_activity_allele(problem);
         let mut genes = vec![sentinel; candidate_slots.len()];
-        let mut slot_to_index = HashMap::<u16, usize>::new();
-        for (idx, slot) in candidate_slots.iter().enumerate() {
-            slot_to_index.insert(*slot, idx);
-        }
         for (slot, floating_choice) in assignments {
-            let idx = *slot_to_index
-                .get(slot)
+            // Repeated slots (one lane per participant) are filled in order.
+            let idx = (0..candidate_slots.len())
+                .find(|&idx| candidate_slots[idx] == *slot && genes[idx] == sentinel)
                 .unwrap_or_else(|| panic!("slot {} is not in candidate slot set", slot));
             genes[idx] = *floating_choice;
         }
@@ -839,106 +1890,1354 @@ mod tests {
     }
 
     #[test]
-    fn input_binding_penalizes_when_predecessor_not_seen() {
-        let mut a = base_activity(0);
-        let mut b = base_activity(1);
-        a.priority = 0.0;
-        b.priority = 0.0;
+    fn input_binding_penalizes_when_predecessor_not_seen() {
+        let mut a = base_activity(0);
+        let mut b = base_activity(1);
+        a.priority = 0.0;
+        b.priority = 0.0;
+
+        b.input_bindings.push(Binding {
+            required_sets: vec![vec![0]],
+            time_scope: TimeScope::SameDay,
+            valid_weekdays: 0b1111111,
+            weight: 100.0,
+        });
+
+        let problem = Problem {
+            activities: vec![a, b],
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![],
+            global_constraints: vec![],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        let valid = chromosome_from_assignments(&problem, &candidate_slots, &[(5, 0), (20, 1)]);
+        let invalid = chromosome_from_assignments(&problem, &candidate_slots, &[(20, 0), (5, 1)]);
+
+        let mut fitness = DiemFitness::new(problem.clone(), candidate_slots.clone());
+
+        let valid_score = fitness
+            .calculate_for_chromosome(&valid, &genotype)
+            .expect("fitness should be computed");
+
+        let mut fitness = DiemFitness::new(problem, candidate_slots);
+        let invalid_score = fitness
+            .calculate_for_chromosome(&invalid, &genotype)
+            .expect("fitness should be computed");
+
+        assert!(
+            valid_score > invalid_score,
+            "input binding should penalize invalid ordering"
+        );
+    }
+
+    #[test]
+    fn weekday_mask_skips_binding_outside_applicable_day() {
+        let mut a = base_activity(0);
+        let mut b = base_activity(1);
+        a.priority = 0.0;
+        b.priority = 0.0;
+
+        b.input_bindings.push(Binding {
+            required_sets: vec![vec![0]],
+            time_scope: TimeScope::SameDay,
+            valid_weekdays: 1 << 0, // Monday only
+            weight: 200.0,
+        });
+
+        let problem = Problem {
+            activities: vec![a, b],
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![],
+            global_constraints: vec![],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96 * 2,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        // Day 1 (Tuesday in solver's weekday mapping), so Monday-only binding is ignored.
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        let chromosome =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(96 + 20, 0), (96 + 5, 1)]);
+
+        let mut with_mask = DiemFitness::new(problem.clone(), candidate_slots.clone());
+        let masked_score = with_mask
+            .calculate_for_chromosome(&chromosome, &genotype)
+            .expect("fitness should be computed");
+
+        // Same binding but applicable every weekday should apply penalty.
+        let mut no_mask_problem = problem;
+        no_mask_problem.activities[1].input_bindings[0].valid_weekdays = 0b1111111;
+        let mut without_mask = DiemFitness::new(no_mask_problem, candidate_slots);
+        let unmasked_score = without_mask
+            .calculate_for_chromosome(&chromosome, &genotype)
+            .expect("fitness should be computed");
+
+        assert!(
+            masked_score > unmasked_score,
+            "weekday mask should prevent penalty on non-applicable days"
+        );
+    }
+
+    #[test]
+    fn markov_reward_applies_within_gap_tolerance() {
+        let mut a = base_activity(0);
+        let mut b = base_activity(1);
+        a.priority = 0.0;
+        b.priority = 0.0;
+
+        let problem = Problem {
+            activities: vec![a, b],
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![],
+            global_constraints: vec![],
+            heatmap: vec![],
+            markov_matrix: vec![(0, 1, 1.0)],
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        let within_gap = chromosome_from_assignments(&problem, &candidate_slots, &[(0, 0), (4, 1)]);
+        let outside_gap =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(0, 0), (5, 1)]);
+
+        let mut fitness = DiemFitness::new(problem.clone(), candidate_slots.clone());
+        let within_score = fitness
+            .calculate_for_chromosome(&within_gap, &genotype)
+            .expect("fitness should be computed");
+
+        let mut fitness = DiemFitness::new(problem, candidate_slots);
+        let outside_score = fitness
+            .calculate_for_chromosome(&outside_gap, &genotype)
+            .expect("fitness should be computed");
+
+        assert!(
+            within_score > outside_score,
+            "markov reward should be applied only within gap tolerance"
+        );
+    }
+
+    #[test]
+    fn precedence_penalizes_successor_outside_lag_window() {
+        let mut workout = base_activity(0);
+        let mut cool_down = base_activity(1);
+        workout.priority = 0.0;
+        cool_down.priority = 0.0;
+
+        let problem = Problem {
+            activities: vec![workout, cool_down],
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![],
+            global_constraints: vec![GlobalConstraint::Precedence {
+                predecessor_id: 0,
+                successor_id: 1,
+                min_lag: 0,
+                max_lag: Some(1),
+                weight: 300.0,
+            }],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        // Workout occupies 10-12 in every schedule.
+        let within_lag =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(10, 0), (13, 1)]);
+        let beyond_max_lag =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(10, 0), (20, 1)]);
+        let before_predecessor =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(10, 0), (4, 1)]);
+
+        let mut fitness = DiemFitness::new(problem, candidate_slots);
+        let within_score = fitness
+            .calculate_for_chromosome(&within_lag, &genotype)
+            .expect("fitness should be computed");
+        let beyond_score = fitness
+            .calculate_for_chromosome(&beyond_max_lag, &genotype)
+            .expect("fitness should be computed");
+        let before_score = fitness
+            .calculate_for_chromosome(&before_predecessor, &genotype)
+            .expect("fitness should be computed");
+
+        assert!(
+            within_score > beyond_score,
+            "successor starting after max_lag should be penalized"
+        );
+        assert!(
+            within_score > before_score,
+            "successor without a preceding predecessor should be penalized"
+        );
+    }
+
+    #[test]
+    fn precedence_min_lag_requires_enough_separation() {
+        let mut study = base_activity(0);
+        let mut review = base_activity(1);
+        study.priority = 0.0;
+        review.priority = 0.0;
+
+        let problem = Problem {
+            activities: vec![study, review],
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![],
+            global_constraints: vec![GlobalConstraint::Precedence {
+                predecessor_id: 0,
+                successor_id: 1,
+                min_lag: 192,
+                max_lag: None,
+                weight: 300.0,
+            }],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96 * 4,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        // Study ends at 12; review must start at 204 or later.
+        let two_days_later =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(10, 0), (210, 1)]);
+        let next_day =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(10, 0), (110, 1)]);
+
+        let mut fitness = DiemFitness::new(problem, candidate_slots);
+        let separated_score = fitness
+            .calculate_for_chromosome(&two_days_later, &genotype)
+            .expect("fitness should be computed");
+        let too_soon_score = fitness
+            .calculate_for_chromosome(&next_day, &genotype)
+            .expect("fitness should be computed");
+
+        assert!(
+            separated_score > too_soon_score,
+            "min_lag should penalize successors scheduled too soon after the predecessor"
+        );
+    }
+
+    #[test]
+    fn exclusion_penalizes_pairs_in_same_day() {
+        let mut leg_day = base_activity(0);
+        let mut long_run = base_activity(1);
+        leg_day.priority = 0.0;
+        long_run.priority = 0.0;
+
+        let problem = Problem {
+            activities: vec![leg_day, long_run],
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![],
+            global_constraints: vec![GlobalConstraint::Exclusion {
+                first: ActivitySelector::Activity(0),
+                second: ActivitySelector::Activity(1),
+                window: ExclusionWindow::Scope(TimeScope::SameDay),
+                weight: 400.0,
+            }],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96 * 2,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        // Exclusion is order-independent within the scope.
+        let same_day = chromosome_from_assignments(&problem, &candidate_slots, &[(40, 1), (60, 0)]);
+        let separate_days =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(40, 1), (96 + 60, 0)]);
+
+        let mut fitness = DiemFitness::new(problem, candidate_slots);
+        let same_day_score = fitness
+            .calculate_for_chromosome(&same_day, &genotype)
+            .expect("fitness should be computed");
+        let separate_score = fitness
+            .calculate_for_chromosome(&separate_days, &genotype)
+            .expect("fitness should be computed");
+
+        assert!(
+            separate_score > same_day_score,
+            "excluded pair on the same day should be penalized"
+        );
+    }
+
+    #[test]
+    fn exclusion_month_scope_counts_each_pair_once() {
+        let mut reading = base_activity(0);
+        let mut journaling = base_activity(1);
+        reading.priority = 0.0;
+        journaling.priority = 0.0;
+
+        let problem = Problem {
+            activities: vec![reading, journaling],
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![],
+            global_constraints: vec![GlobalConstraint::Exclusion {
+                first: ActivitySelector::Category(0),
+                second: ActivitySelector::Activity(1),
+                window: ExclusionWindow::Scope(TimeScope::SameMonth),
+                weight: 10.0,
+            }],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96 * 3,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let fitness = DiemFitness::new(problem, candidate_slots);
+        // Journaling matches both selectors: reading pairs with each journaling entry,
+        // and the two journaling entries pair with each other, once.
+        let breakdown = fitness.evaluate_placements(&[(0, 10), (1, 100), (1, 250)], 0);
+
+        assert_eq!(breakdown.constraint_penalty, 30.0);
+    }
+
+    #[test]
+    fn exclusion_lag_window_matches_categories_after_first_ends() {
+        let mut late_shift = base_activity(0);
+        late_shift.priority = 0.0;
+        late_shift.category_id = 3;
+        late_shift.duration_slots = 8;
+        let mut meeting = base_activity(1);
+        meeting.priority = 0.0;
+        meeting.category_id = 7;
+
+        let problem = Problem {
+            activities: vec![late_shift, meeting],
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![],
+            global_constraints: vec![GlobalConstraint::Exclusion {
+                first: ActivitySelector::Category(3),
+                second: ActivitySelector::Category(7),
+                window: ExclusionWindow::Lag {
+                    min_lag: 0,
+                    max_lag: 96,
+                },
+                weight: 400.0,
+            }],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96 * 3,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        // Shift ends at 88; a meeting at 140 falls inside the lag window.
+        let next_morning =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(80, 0), (140, 1)]);
+        let two_days_later =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(80, 0), (200, 1)]);
+        let meeting_before_shift =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(60, 1), (80, 0)]);
+
+        let mut fitness = DiemFitness::new(problem, candidate_slots);
+        let next_morning_score = fitness
+            .calculate_for_chromosome(&next_morning, &genotype)
+            .expect("fitness should be computed");
+        let later_score = fitness
+            .calculate_for_chromosome(&two_days_later, &genotype)
+            .expect("fitness should be computed");
+        let before_score = fitness
+            .calculate_for_chromosome(&meeting_before_shift, &genotype)
+            .expect("fitness should be computed");
+
+        assert!(
+            later_score > next_morning_score,
+            "meeting inside the lag window after the shift should be penalized"
+        );
+        assert_eq!(
+            later_score, before_score,
+            "lag windows only look forward from the first occurrence"
+        );
+    }
+
+    #[test]
+    fn occurrence_spacing_penalizes_back_to_back_repeats() {
+        let mut strength = base_activity(0);
+        strength.priority = 0.0;
+        strength.occurrence_spacing.push(OccurrenceSpacing {
+            min_gap: 192,
+            max_gap: None,
+            weight: 250.0,
+        });
+
+        let problem = Problem {
+            activities: vec![strength],
+            floating_indices: vec![0],
+            fixed_indices: vec![],
+            global_constraints: vec![],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96 * 4,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        let spaced = chromosome_from_assignments(&problem, &candidate_slots, &[(40, 0), (240, 0)]);
+        let stacked = chromosome_from_assignments(&problem, &candidate_slots, &[(40, 0), (42, 0)]);
+
+        let mut fitness = DiemFitness::new(problem, candidate_slots);
+        let spaced_score = fitness
+            .calculate_for_chromosome(&spaced, &genotype)
+            .expect("fitness should be computed");
+        let stacked_score = fitness
+            .calculate_for_chromosome(&stacked, &genotype)
+            .expect("fitness should be computed");
+
+        assert!(
+            spaced_score > stacked_score,
+            "repeats closer than min_gap should be penalized"
+        );
+    }
+
+    #[test]
+    fn occurrence_spacing_schedule_applies_per_gap_index() {
+        let mut review = base_activity(0);
+        review.priority = 0.0;
+        for days in [1u16, 3] {
+            review.occurrence_spacing.push(OccurrenceSpacing {
+                min_gap: days * 96,
+                max_gap: Some(days * 96),
+                weight: 250.0,
+            });
+        }
+
+        let problem = Problem {
+            activities: vec![review],
+            floating_indices: vec![0],
+            fixed_indices: vec![],
+            global_constraints: vec![],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96 * 8,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        // Gaps of 1 day then 3 days, and the last entry repeats for the third gap.
+        let on_schedule = chromosome_from_assignments(
+            &problem,
+            &candidate_slots,
+            &[(10, 0), (106, 0), (394, 0), (682, 0)],
+        );
+        let evenly_spaced = chromosome_from_assignments(
+            &problem,
+            &candidate_slots,
+            &[(10, 0), (106, 0), (202, 0), (298, 0)],
+        );
+
+        let mut fitness = DiemFitness::new(problem, candidate_slots);
+        let on_schedule_score = fitness
+            .calculate_for_chromosome(&on_schedule, &genotype)
+            .expect("fitness should be computed");
+        let evenly_spaced_score = fitness
+            .calculate_for_chromosome(&evenly_spaced, &genotype)
+            .expect("fitness should be computed");
+
+        assert!(
+            on_schedule_score > evenly_spaced_score,
+            "gap schedule should follow the per-index spacing entries"
+        );
+    }
+
+    #[test]
+    fn continuous_busy_limit_requires_break_after_long_streak() {
+        let mut activities = vec![base_activity(0), base_activity(1), base_activity(2)];
+        for activity in &mut activities {
+            activity.priority = 0.0;
+            activity.duration_slots = 4;
+        }
+
+        let problem = Problem {
+            activities,
+            floating_indices: vec![0, 1, 2],
+            fixed_indices: vec![],
+            global_constraints: vec![GlobalConstraint::ContinuousBusyLimit {
+                category_id: None,
+                max_busy_slots: 8,
+                min_break_slots: 2,
+                weight: 100.0,
+            }],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        let chained =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(10, 0), (14, 1), (19, 2)]);
+        let with_break =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(10, 0), (14, 1), (20, 2)]);
+
+        let mut fitness = DiemFitness::new(problem, candidate_slots);
+        let chained_score = fitness
+            .calculate_for_chromosome(&chained, &genotype)
+            .expect("fitness should be computed");
+        let break_score = fitness
+            .calculate_for_chromosome(&with_break, &genotype)
+            .expect("fitness should be computed");
+
+        assert!(
+            break_score > chained_score,
+            "a gap shorter than min_break_slots should not reset the busy streak"
+        );
+    }
+
+    #[test]
+    fn buffer_before_penalizes_back_to_back_start() {
+        let mut deep_work = base_activity(0);
+        deep_work.priority = 0.0;
+        let mut meeting = base_activity(1);
+        meeting.priority = 0.0;
+        meeting.buffer_before = 1;
+
+        let problem = Problem {
+            activities: vec![deep_work, meeting],
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![],
+            global_constraints: vec![],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        let back_to_back =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(10, 0), (12, 1)]);
+        let with_prep =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(10, 0), (13, 1)]);
+
+        let mut fitness = DiemFitness::new(problem, candidate_slots);
+
+        fitness.objective = ObjectiveMode::Weighted;
+        let back_to_back_score = fitness
+            .calculate_for_chromosome(&back_to_back, &genotype)
+            .expect("fitness should be computed");
+        let with_prep_score = fitness
+            .calculate_for_chromosome(&with_prep, &genotype)
+            .expect("fitness should be computed");
+
+        assert!(
+            with_prep_score > back_to_back_score,
+            "meeting buffer should require free prep time before it"
+        );
+        assert!(
+            back_to_back_score > -100_000,
+            "buffer intrusion should stay below the overlap penalty"
+        );
+    }
+
+    #[test]
+    fn workload_limit_splits_busy_time_at_real_midnight() {
+        let mut night_shift = base_activity(0);
+        night_shift.priority = 0.0;
+        night_shift.duration_slots = 8;
+        night_shift.category_id = 2;
+
+        let mut problem = Problem {
+            activities: vec![night_shift],
+            floating_indices: vec![0],
+            fixed_indices: vec![],
+            global_constraints: vec![GlobalConstraint::WorkloadLimit {
+                scope: TimeScope::SameDay,
+                category_id: Some(2),
+                max_busy_slots: 4,
+                hard: false,
+                penalty_weight: 50.0,
+            }],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96 * 2,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        // Horizon slot 44 is 23:00 once the horizon starts at 12:00.
+        let chromosome = chromosome_from_assignments(&problem, &candidate_slots, &[(44, 0)]);
+
+        let mut fitness = DiemFitness::new(problem.clone(), candidate_slots.clone());
+
+        fitness.objective = ObjectiveMode::Weighted;
+        let aligned_to_slot_zero = fitness
+            .calculate_for_chromosome(&chromosome, &genotype)
+            .expect("fitness should be computed");
+
+        problem.day_start_offset = 48;
+        let mut fitness = DiemFitness::new(problem, candidate_slots);
+        fitness.objective = ObjectiveMode::Weighted;
+        let aligned_to_midnight = fitness
+            .calculate_for_chromosome(&chromosome, &genotype)
+            .expect("fitness should be computed");
+
+        // 8 slots in one day exceed the cap by 4; split 4 + 4 across midnight fits exactly.
+        let penalty_removed = aligned_to_midnight - aligned_to_slot_zero;
+        assert!(
+            (199..=201).contains(&penalty_removed),
+            "expected the 4-slot excess (200 pts) to disappear, diff was {}",
+            penalty_removed
+        );
+    }
+
+    #[test]
+    fn hard_workload_limit_outweighs_soft_limit() {
+        let mut activities = vec![base_activity(0), base_activity(1)];
+        for activity in &mut activities {
+            activity.priority = 0.0;
+            activity.duration_slots = 4;
+        }
+
+        let mut problem = Problem {
+            activities,
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![],
+            global_constraints: vec![GlobalConstraint::WorkloadLimit {
+                scope: TimeScope::SameDay,
+                category_id: None,
+                max_busy_slots: 6,
+                hard: false,
+                penalty_weight: 10.0,
+            }],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        let chromosome =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(10, 0), (30, 1)]);
+
+        let mut fitness = DiemFitness::new(problem.clone(), candidate_slots.clone());
+
+        fitness.objective = ObjectiveMode::Weighted;
+        let soft_score = fitness
+            .calculate_for_chromosome(&chromosome, &genotype)
+            .expect("fitness should be computed");
+
+        if let GlobalConstraint::WorkloadLimit { hard, .. } = &mut problem.global_constraints[0] {
+            *hard = true;
+        }
+        let mut fitness = DiemFitness::new(problem, candidate_slots);
+        fitness.objective = ObjectiveMode::Weighted;
+        let hard_score = fitness
+            .calculate_for_chromosome(&chromosome, &genotype)
+            .expect("fitness should be computed");
+
+        assert!(
+            soft_score > -100,
+            "soft cap should cost penalty_weight per excess slot"
+        );
+        assert!(
+            hard_score < -100_000,
+            "hard cap should use the hard-constraint penalty"
+        );
+    }
+
+    #[test]
+    fn free_time_target_discourages_packing_available_time() {
+        let mut activities = vec![base_activity(0), base_activity(1)];
+        for activity in &mut activities {
+            activity.priority = 1.0;
+            activity.duration_slots = 8;
+        }
+
+        let problem = Problem {
+            activities,
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![],
+            global_constraints: vec![
+                GlobalConstraint::ForbiddenZone {
+                    start: 0,
+                    end: 76,
+                    activity_id: None,
+                },
+                GlobalConstraint::FreeTimeTarget {
+                    scope: TimeScope::SameDay,
+                    min_free_slots: 8,
+                    weight: 20.0,
+                },
+            ],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        // 20 available slots: one 8-slot block leaves 12 free, two leave only 4.
+        let one_block = chromosome_from_assignments(&problem, &candidate_slots, &[(76, 0)]);
+        let packed = chromosome_from_assignments(&problem, &candidate_slots, &[(76, 0), (84, 1)]);
+
+        let mut fitness = DiemFitness::new(problem, candidate_slots);
+        let one_block_score = fitness
+            .calculate_for_chromosome(&one_block, &genotype)
+            .expect("fitness should be computed");
+        let packed_score = fitness
+            .calculate_for_chromosome(&packed, &genotype)
+            .expect("fitness should be computed");
+
+        assert!(
+            one_block_score > packed_score,
+            "free time target should outweigh the reward of filling the remaining slots"
+        );
+    }
+
+    #[test]
+    fn category_switch_cost_and_batching_favor_focused_blocks() {
+        let mut activities = vec![
+            base_activity(0),
+            base_activity(1),
+            base_activity(2),
+            base_activity(3),
+        ];
+        for activity in &mut activities {
+            activity.priority = 0.0;
+        }
+        activities[0].category_id = 1; // admin
+        activities[1].category_id = 1; // admin
+        activities[2].category_id = 2; // errands
+        activities[3].category_id = 2; // errands
+
+        let mut problem = Problem {
+            activities,
+            floating_indices: vec![0, 1, 2, 3],
+            fixed_indices: vec![],
+            global_constraints: vec![],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![(1, 2, 30.0), (2, 1, 30.0)],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        let alternating = chromosome_from_assignments(
+            &problem,
+            &candidate_slots,
+            &[(10, 0), (12, 2), (14, 1), (16, 3)],
+        );
+        let batched = chromosome_from_assignments(
+            &problem,
+            &candidate_slots,
+            &[(10, 0), (12, 1), (14, 2), (16, 3)],
+        );
+
+        let mut fitness = DiemFitness::new(problem.clone(), candidate_slots.clone());
+        let alternating_score = fitness
+            .calculate_for_chromosome(&alternating, &genotype)
+            .expect("fitness should be computed");
+        let batched_score = fitness
+            .calculate_for_chromosome(&batched, &genotype)
+            .expect("fitness should be computed");
+        assert!(
+            batched_score > alternating_score,
+            "switch costs should favor grouping categories"
+        );
+
+        problem.category_switch_costs.clear();
+        problem.category_batching_bonus = vec![(2, 15.0)];
+        let mut fitness = DiemFitness::new(problem, candidate_slots);
+        let alternating_score = fitness
+            .calculate_for_chromosome(&alternating, &genotype)
+            .expect("fitness should be computed");
+        let batched_score = fitness
+            .calculate_for_chromosome(&batched, &genotype)
+            .expect("fitness should be computed");
+        assert!(
+            batched_score > alternating_score,
+            "batching bonus should reward adjacent same-category errands"
+        );
+    }
+
+    #[test]
+    fn travel_time_required_between_locations() {
+        let mut office_meeting = base_activity(0);
+        office_meeting.priority = 0.0;
+        office_meeting.duration_slots = 4;
+        office_meeting.location_id = Some(1);
+        let mut gym = base_activity(1);
+        gym.priority = 0.0;
+        gym.location_id = Some(2);
 
-        b.input_bindings.push(Binding {
-            required_sets: vec![vec![0]],
-            time_scope: TimeScope::SameDay,
-            valid_weekdays: 0b1111111,
-            weight: 100.0,
-        });
+        let problem = Problem {
+            activities: vec![office_meeting, gym],
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![],
+            global_constraints: vec![],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![(1, 2, 2)],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        // Meeting ends at 72 (18:00).
+        let no_travel_gap =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(68, 0), (72, 1)]);
+        let travel_gap =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(68, 0), (74, 1)]);
+        // Reverse direction falls back to the forward entry.
+        let reverse_no_gap =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(66, 1), (68, 0)]);
+
+        let mut fitness = DiemFitness::new(problem, candidate_slots);
+        let no_gap_score = fitness
+            .calculate_for_chromosome(&no_travel_gap, &genotype)
+            .expect("fitness should be computed");
+        let gap_score = fitness
+            .calculate_for_chromosome(&travel_gap, &genotype)
+            .expect("fitness should be computed");
+        let reverse_score = fitness
+            .calculate_for_chromosome(&reverse_no_gap, &genotype)
+            .expect("fitness should be computed");
+
+        assert!(gap_score >= 0, "travel gap should satisfy the matrix entry");
+        assert!(
+            no_gap_score < -100_000,
+            "back-to-back items across town should be infeasible"
+        );
+        assert!(
+            reverse_score < -100_000,
+            "reverse travel should mirror the forward entry"
+        );
+    }
+
+    #[test]
+    fn travel_time_counts_toward_workload() {
+        let mut office = base_activity(0);
+        office.priority = 0.0;
+        office.location_id = Some(1);
+        let mut gym = base_activity(1);
+        gym.priority = 0.0;
+        gym.location_id = Some(2);
 
         let problem = Problem {
-            activities: vec![a, b],
+            activities: vec![office, gym],
             floating_indices: vec![0, 1],
             fixed_indices: vec![],
+            global_constraints: vec![GlobalConstraint::WorkloadLimit {
+                scope: TimeScope::SameDay,
+                category_id: None,
+                max_busy_slots: 4,
+                hard: false,
+                penalty_weight: 50.0,
+            }],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![(1, 2, 3)],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        let chromosome =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(10, 0), (20, 1)]);
+
+        let mut fitness = DiemFitness::new(problem.clone(), candidate_slots.clone());
+        let with_travel = fitness
+            .calculate_for_chromosome(&chromosome, &genotype)
+            .expect("fitness should be computed");
+
+        let mut same_place = problem;
+        same_place.activities[1].location_id = Some(1);
+        let mut fitness = DiemFitness::new(same_place, candidate_slots);
+        let without_travel = fitness
+            .calculate_for_chromosome(&chromosome, &genotype)
+            .expect("fitness should be computed");
+
+        assert!(
+            without_travel - with_travel >= 149,
+            "three travel slots should count as busy time against the daily cap"
+        );
+    }
+
+    #[test]
+    fn resource_capacity_penalizes_concurrent_demand() {
+        let mut errand = base_activity(0);
+        errand.priority = 0.0;
+        errand.duration_slots = 4;
+        errand.resource_requirements.push(ResourceRequirement {
+            resource_id: 9,
+            amount: 1,
+        });
+        // A partner's fixed drive holds the same car.
+        let mut partner_drive = base_activity(1);
+        partner_drive.activity_type = ActivityType::Fixed;
+        partner_drive.assigned_start = Some(20);
+        partner_drive.duration_slots = 4;
+        partner_drive.resource_requirements = errand.resource_requirements.clone();
+
+        let mut problem = Problem {
+            activities: vec![errand, partner_drive],
+            floating_indices: vec![0],
+            fixed_indices: vec![1],
             global_constraints: vec![],
             heatmap: vec![],
             markov_matrix: vec![],
             total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
 
         let candidate_slots = build_candidate_start_slots(&problem);
         let genotype = genotype_for(&problem, &candidate_slots);
-        let valid = chromosome_from_assignments(&problem, &candidate_slots, &[(5, 0), (20, 1)]);
-        let invalid = chromosome_from_assignments(&problem, &candidate_slots, &[(20, 0), (5, 1)]);
+        // Errand 18-22 shares two slots with the drive at 20-24.
+        let shared_window = chromosome_from_assignments(&problem, &candidate_slots, &[(18, 0)]);
+        let back_to_back = chromosome_from_assignments(&problem, &candidate_slots, &[(16, 0)]);
 
         let mut fitness = DiemFitness::new(problem.clone(), candidate_slots.clone());
 
-        let valid_score = fitness
-            .calculate_for_chromosome(&valid, &genotype)
+        fitness.objective = ObjectiveMode::Weighted;
+        let overlap_only_score = fitness
+            .calculate_for_chromosome(&shared_window, &genotype)
             .expect("fitness should be computed");
 
+        problem.resources.push(Resource {
+            id: 9,
+            name: "Car".to_string(),
+            capacity: 1,
+        });
         let mut fitness = DiemFitness::new(problem, candidate_slots);
-        let invalid_score = fitness
-            .calculate_for_chromosome(&invalid, &genotype)
+        fitness.objective = ObjectiveMode::Weighted;
+        let contended_score = fitness
+            .calculate_for_chromosome(&shared_window, &genotype)
+            .expect("fitness should be computed");
+        let handover_score = fitness
+            .calculate_for_chromosome(&back_to_back, &genotype)
             .expect("fitness should be computed");
 
+        let resource_penalty = (overlap_only_score - contended_score) as f32;
         assert!(
-            valid_score > invalid_score,
-            "input binding should penalize invalid ordering"
+            (199_000.0..=201_000.0).contains(&resource_penalty),
+            "two slots over capacity should add two unit-slot penalties, got {}",
+            resource_penalty
+        );
+        assert!(
+            handover_score >= 0,
+            "releasing the car as the next holder starts should be within capacity"
         );
     }
 
     #[test]
-    fn weekday_mask_skips_binding_outside_applicable_day() {
-        let mut a = base_activity(0);
-        let mut b = base_activity(1);
-        a.priority = 0.0;
-        b.priority = 0.0;
+    fn shared_activity_requires_every_participant_to_be_free() {
+        let mut work = base_activity(0);
+        work.activity_type = ActivityType::Fixed;
+        work.assigned_start = Some(4);
+        work.duration_slots = 4;
+        work.participant_ids = vec![0];
+        let mut dinner = base_activity(1);
+        dinner.participant_ids = vec![0, 1];
+        let mut homework = base_activity(2);
+        homework.participant_ids = vec![1];
+        let mut chores = base_activity(3);
+        chores.participant_ids = vec![0];
 
-        b.input_bindings.push(Binding {
-            required_sets: vec![vec![0]],
-            time_scope: TimeScope::SameDay,
-            valid_weekdays: 1 << 0, // Monday only
-            weight: 200.0,
-        });
+        let problem = Problem {
+            activities: vec![work, dinner, homework, chores],
+            floating_indices: vec![1, 2, 3],
+            fixed_indices: vec![0],
+            global_constraints: vec![],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![
+                Participant {
+                    id: 0,
+                    name: "Parent".to_string(),
+                },
+                Participant {
+                    id: 1,
+                    name: "Child".to_string(),
+                },
+            ],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genotype = genotype_for(&problem, &candidate_slots);
+        // Child does homework while the parent works, then both have dinner.
+        let joint = chromosome_from_assignments(&problem, &candidate_slots, &[(4, 1), (8, 0)]);
+        // Dinner during the parent's work block.
+        let clash = chromosome_from_assignments(&problem, &candidate_slots, &[(4, 0), (8, 1)]);
+        // Parent and child start separate activities in the same slot.
+        let parallel = chromosome_from_assignments(&problem, &candidate_slots, &[(12, 1), (12, 2)]);
+
+        let mut fitness = DiemFitness::new(problem, candidate_slots);
+        let joint_score = fitness
+            .calculate_for_chromosome(&joint, &genotype)
+            .expect("fitness should be computed");
+        let clash_score = fitness
+            .calculate_for_chromosome(&clash, &genotype)
+            .expect("fitness should be computed");
+        let parallel_score = fitness
+            .calculate_for_chromosome(&parallel, &genotype)
+            .expect("fitness should be computed");
+
+        assert!(joint_score >= 0, "got {}", joint_score);
+        assert!(clash_score < -100_000, "got {}", clash_score);
+        assert!(parallel_score >= 0, "got {}", parallel_score);
+    }
+
+    #[test]
+    fn busy_limits_apply_to_each_participant_separately() {
+        let mut meeting = base_activity(0);
+        meeting.duration_slots = 4;
+        meeting.participant_ids = vec![0];
+        let mut class = base_activity(1);
+        class.duration_slots = 4;
+        class.participant_ids = vec![1];
 
         let problem = Problem {
-            activities: vec![a, b],
+            activities: vec![meeting, class],
             floating_indices: vec![0, 1],
             fixed_indices: vec![],
+            global_constraints: vec![
+                GlobalConstraint::ContinuousBusyLimit {
+                    category_id: None,
+                    max_busy_slots: 6,
+                    min_break_slots: 2,
+                    weight: 10.0,
+                },
+                GlobalConstraint::WorkloadLimit {
+                    scope: TimeScope::SameDay,
+                    category_id: None,
+                    max_busy_slots: 8,
+                    hard: false,
+                    penalty_weight: 10.0,
+                },
+            ],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![
+                Participant {
+                    id: 0,
+                    name: "Parent".to_string(),
+                },
+                Participant {
+                    id: 1,
+                    name: "Child".to_string(),
+                },
+            ],
+        };
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let fitness = DiemFitness::new(problem, candidate_slots);
+
+        // Back to back on the shared timeline, but each person gets a four-slot break.
+        let interleaved = fitness.evaluate_placements(&[(0, 10), (1, 14), (0, 18), (1, 22)], 0);
+        assert_eq!(interleaved.constraint_penalty, 0.0);
+        assert_eq!(interleaved.free_slots, 2 * 96 - 16);
+
+        // The same four blocks for one person form one 16-slot streak on an 8-slot cap.
+        let one_person = fitness.evaluate_placements(&[(0, 10), (0, 14), (0, 18), (0, 22)], 0);
+        assert_eq!(one_person.constraint_penalty, 100.0 + 80.0);
+    }
+
+    #[test]
+    fn compatible_activities_overlap_without_penalty() {
+        let mut commute = base_activity(0);
+        commute.activity_type = ActivityType::Fixed;
+        commute.assigned_start = Some(30);
+        commute.duration_slots = 4;
+        commute.category_id = 5;
+        let mut podcast = base_activity(1);
+        podcast.priority = 0.0;
+        let mut deep_work = base_activity(2);
+        deep_work.priority = 0.0;
+
+        let problem = Problem {
+            activities: vec![commute, podcast, deep_work],
+            floating_indices: vec![1, 2],
+            fixed_indices: vec![0],
             global_constraints: vec![],
             heatmap: vec![],
             markov_matrix: vec![],
-            total_slots: 96 * 2,
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
 
-        // Day 1 (Tuesday in solver's weekday mapping), so Monday-only binding is ignored.
-        let candidate_slots = build_candidate_start_slots(&problem);
+        let mut attention_free = problem.clone();
+        attention_free.activities[1].attention_free = true;
+
+        // Commute slots only become start candidates once an activity may overlap them.
+        let candidate_slots = build_candidate_start_slots(&attention_free);
         let genotype = genotype_for(&problem, &candidate_slots);
-        let chromosome =
-            chromosome_from_assignments(&problem, &candidate_slots, &[(96 + 20, 0), (96 + 5, 1)]);
+        let podcast_on_commute =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(31, 0)]);
+        let deep_work_on_commute =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(31, 1)]);
+
+        let mut incompatible = DiemFitness::new(problem.clone(), candidate_slots.clone());
+        let incompatible_score = incompatible
+            .calculate_for_chromosome(&podcast_on_commute, &genotype)
+            .expect("fitness should be computed");
 
-        let mut with_mask = DiemFitness::new(problem.clone(), candidate_slots.clone());
-        let masked_score = with_mask
-            .calculate_for_chromosome(&chromosome, &genotype)
+        let mut fitness = DiemFitness::new(attention_free, candidate_slots.clone());
+        let attention_free_score = fitness
+            .calculate_for_chromosome(&podcast_on_commute, &genotype)
+            .expect("fitness should be computed");
+        let conflicting_score = fitness
+            .calculate_for_chromosome(&deep_work_on_commute, &genotype)
             .expect("fitness should be computed");
 
-        // Same binding but applicable every weekday should apply penalty.
-        let mut no_mask_problem = problem;
-        no_mask_problem.activities[1].input_bindings[0].valid_weekdays = 0b1111111;
-        let mut without_mask = DiemFitness::new(no_mask_problem, candidate_slots);
-        let unmasked_score = without_mask
-            .calculate_for_chromosome(&chromosome, &genotype)
+        let mut by_category = problem;
+        by_category.activities[1].overlap_compatible_categories = vec![5];
+        let mut fitness = DiemFitness::new(by_category, candidate_slots);
+        let category_score = fitness
+            .calculate_for_chromosome(&podcast_on_commute, &genotype)
             .expect("fitness should be computed");
 
+        assert!(incompatible_score < -100_000);
         assert!(
-            masked_score > unmasked_score,
-            "weekday mask should prevent penalty on non-applicable days"
+            attention_free_score >= 0,
+            "attention-free overlap is allowed"
+        );
+        assert!(
+            category_score >= 0,
+            "compatible category overlap is allowed"
+        );
+        assert!(
+            conflicting_score < -100_000,
+            "other activities still conflict with the commute"
         );
     }
 
     #[test]
-    fn markov_reward_applies_within_gap_tolerance() {
+    fn overlapping_activities_count_shared_slots_once() {
+        let mut commute = base_activity(0);
+        commute.activity_type = ActivityType::Fixed;
+        commute.assigned_start = Some(30);
+        commute.duration_slots = 4;
+        let mut podcast = base_activity(1);
+        podcast.attention_free = true;
+
+        let problem = Problem {
+            activities: vec![commute, podcast],
+            floating_indices: vec![1],
+            fixed_indices: vec![0],
+            global_constraints: vec![
+                GlobalConstraint::WorkloadLimit {
+                    scope: TimeScope::SameDay,
+                    category_id: None,
+                    max_busy_slots: 4,
+                    hard: false,
+                    penalty_weight: 10.0,
+                },
+                GlobalConstraint::FreeTimeTarget {
+                    scope: TimeScope::SameDay,
+                    min_free_slots: 92,
+                    weight: 10.0,
+                },
+            ],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let fitness = DiemFitness::new(problem, candidate_slots);
+
+        // The podcast runs inside the commute, so the day stays four slots busy.
+        let during = fitness.evaluate_placements(&[(1, 31)], 0);
+        assert_eq!(during.free_slots, 92);
+        assert_eq!(during.constraint_penalty, 0.0);
+
+        // Straddling the commute's end adds only the slot past it.
+        let straddling = fitness.evaluate_placements(&[(1, 33)], 0);
+        assert_eq!(straddling.free_slots, 91);
+        assert_eq!(straddling.constraint_penalty, 20.0);
+    }
+
+    #[test]
+    fn travel_is_not_free_time() {
+        let mut office = base_activity(0);
+        office.activity_type = ActivityType::Fixed;
+        office.assigned_start = Some(10);
+        office.duration_slots = 4;
+        office.location_id = Some(1);
+        let mut gym = base_activity(1);
+        gym.location_id = Some(2);
+        gym.duration_slots = 4;
+
+        let problem = Problem {
+            activities: vec![office, gym],
+            floating_indices: vec![1],
+            fixed_indices: vec![0],
+            global_constraints: vec![GlobalConstraint::FreeTimeTarget {
+                scope: TimeScope::SameDay,
+                min_free_slots: 90,
+                weight: 10.0,
+            }],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![(1, 2, 3)],
+            resources: vec![],
+            participants: vec![],
+        };
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let fitness = DiemFitness::new(problem, candidate_slots);
+
+        // Office 10-14, travel 17-20, gym 20-24: 11 slots taken, 85 free, 5 short.
+        let breakdown = fitness.evaluate_placements(&[(1, 20)], 0);
+        assert_eq!(breakdown.hard_violations, 0);
+        assert_eq!(breakdown.free_slots, 85);
+        assert_eq!(breakdown.constraint_penalty, 50.0);
+    }
+
+    #[test]
+    fn lexicographic_objective_ranks_hard_violations_first() {
         let mut a = base_activity(0);
+        a.priority = 1_000.0;
+        a.duration_slots = 4;
         let mut b = base_activity(1);
-        a.priority = 0.0;
-        b.priority = 0.0;
+        b.priority = 1_000.0;
+        b.duration_slots = 4;
+        a.user_frequency_constraints.push(UserFrequencyConstraint {
+            scope: TimeScope::SameDay,
+            min_count: None,
+            max_count: Some(0),
+            deadline_end: None,
+            penalty_weight: 50_000_000.0,
+        });
 
         let problem = Problem {
             activities: vec![a, b],
@@ -946,32 +3245,158 @@ mod tests {
             fixed_indices: vec![],
             global_constraints: vec![],
             heatmap: vec![],
-            markov_matrix: vec![(0, 1, 1.0)],
+            markov_matrix: vec![],
             total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
 
         let candidate_slots = build_candidate_start_slots(&problem);
         let genotype = genotype_for(&problem, &candidate_slots);
-        let within_gap = chromosome_from_assignments(&problem, &candidate_slots, &[(0, 0), (4, 1)]);
-        let outside_gap =
-            chromosome_from_assignments(&problem, &candidate_slots, &[(0, 0), (5, 1)]);
+        // Violates the huge user cap but places everything without overlap.
+        let user_violation =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(0, 0), (10, 1)]);
+        // Overlaps once and otherwise earns the same rewards.
+        let overlapping =
+            chromosome_from_assignments(&problem, &candidate_slots, &[(20, 1), (22, 1)]);
+        let neither = chromosome_from_assignments(&problem, &candidate_slots, &[(20, 1)]);
 
         let mut fitness = DiemFitness::new(problem.clone(), candidate_slots.clone());
-        let within_score = fitness
-            .calculate_for_chromosome(&within_gap, &genotype)
+        let user_violation_score = fitness
+            .calculate_for_chromosome(&user_violation, &genotype)
             .expect("fitness should be computed");
-
-        let mut fitness = DiemFitness::new(problem, candidate_slots);
-        let outside_score = fitness
-            .calculate_for_chromosome(&outside_gap, &genotype)
+        let overlapping_score = fitness
+            .calculate_for_chromosome(&overlapping, &genotype)
+            .expect("fitness should be computed");
+        let neither_score = fitness
+            .calculate_for_chromosome(&neither, &genotype)
             .expect("fitness should be computed");
 
+        assert!(user_violation_score > overlapping_score);
+        assert!(neither_score > user_violation_score);
+
+        let mut weighted = DiemFitness::new(problem, candidate_slots);
+        weighted.objective = ObjectiveMode::Weighted;
+        let weighted_user_violation = weighted
+            .calculate_for_chromosome(&user_violation, &genotype)
+            .expect("fitness should be computed");
+        let weighted_overlapping = weighted
+            .calculate_for_chromosome(&overlapping, &genotype)
+            .expect("fitness should be computed");
         assert!(
-            within_score > outside_score,
-            "markov reward should be applied only within gap tolerance"
+            weighted_user_violation < weighted_overlapping,
+            "a large penalty weight outranks an overlap under the weighted sum"
+        );
+    }
+
+    #[test]
+    fn tier_rank_orders_schedules_past_the_packed_limits() {
+        let fewer_hard = FitnessBreakdown {
+            hard_violations: 5_000,
+            ..FitnessBreakdown::default()
+        };
+        let more_hard = FitnessBreakdown {
+            hard_violations: 6_000,
+            ..FitnessBreakdown::default()
+        };
+        let lower_user = FitnessBreakdown {
+            constraint_penalty: 10_000_000.0,
+            ..FitnessBreakdown::default()
+        };
+        let higher_user = FitnessBreakdown {
+            constraint_penalty: 20_000_000.0,
+            ..FitnessBreakdown::default()
+        };
+
+        // Both pairs saturate their packed tier, so the GA score cannot tell them apart.
+        assert_eq!(
+            fewer_hard.lexicographic_score(),
+            more_hard.lexicographic_score()
+        );
+        assert_eq!(
+            lower_user.lexicographic_score(),
+            higher_user.lexicographic_score()
+        );
+        assert!(fewer_hard.lexicographic_rank() > more_hard.lexicographic_rank());
+        assert!(lower_user.lexicographic_rank() > higher_user.lexicographic_rank());
+        assert!(higher_user.lexicographic_rank() > fewer_hard.lexicographic_rank());
+    }
+
+    #[test]
+    fn size_based_rules_count_one_violation_each() {
+        let mut a = base_activity(0);
+        a.duration_slots = 4;
+
+        let problem = Problem {
+            activities: vec![a],
+            floating_indices: vec![0],
+            fixed_indices: vec![],
+            global_constraints: vec![GlobalConstraint::CumulativeTime {
+                activity_id: Some(0),
+                category_id: None,
+                period_slots: 96,
+                min_duration: 12,
+                max_duration: 32,
+                deadline_end: None,
+            }],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let fitness = DiemFitness::new(problem, candidate_slots);
+
+        // Eight missing slots are one violated rule, still penalized per slot.
+        let breakdown = fitness.evaluate_placements(&[(0, 8)], 0);
+        assert_eq!(breakdown.hard_violations, 1);
+        assert_eq!(
+            breakdown.hard_penalty,
+            8.0 * DiemFitness::PENALTY_CUMULATIVE
         );
     }
 
+    #[test]
+    fn encode_round_trips_greedy_placements() {
+        let mut a = base_activity(0);
+        a.priority = 2.0;
+        let b = base_activity(1);
+
+        let problem = Problem {
+            activities: vec![a, b],
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![],
+            global_constraints: vec![],
+            heatmap: vec![(0, 6, 0.9), (1, 6, 0.9)],
+            markov_matrix: vec![],
+            total_slots: 24,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let fitness = DiemFitness::new(problem, candidate_slots);
+        let placements = greedy_schedule(&fitness.problem);
+        let genes = fitness.encode(&placements);
+
+        assert_eq!(placements.len(), 2);
+        assert_eq!(fitness.decode(&genes), placements);
+        assert_eq!(fitness.evaluate(&genes).hard_violations, 0);
+    }
+
     #[test]
     fn user_frequency_min_penalizes_shortfall() {
         let mut a = base_activity(0);
@@ -992,6 +3417,12 @@ mod tests {
             heatmap: vec![],
             markov_matrix: vec![],
             total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
 
         let candidate_slots = build_candidate_start_slots(&problem);
@@ -1038,6 +3469,12 @@ mod tests {
             heatmap: vec![],
             markov_matrix: vec![],
             total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
 
         let candidate_slots = build_candidate_start_slots(&problem);
@@ -1082,6 +3519,12 @@ mod tests {
             heatmap: vec![],
             markov_matrix: vec![],
             total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
 
         let candidate_slots = build_candidate_start_slots(&problem);
@@ -1127,6 +3570,12 @@ mod tests {
             heatmap: vec![],
             markov_matrix: vec![],
             total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
 
         let candidate_slots = build_candidate_start_slots(&problem);
@@ -1171,6 +3620,12 @@ mod tests {
             heatmap: vec![],
             markov_matrix: vec![],
             total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
 
         let candidate_slots = build_candidate_start_slots(&problem);
@@ -1207,6 +3662,12 @@ mod tests {
             heatmap: vec![],
             markov_matrix: vec![],
             total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
 
         let candidate_slots = build_candidate_start_slots(&problem);
@@ -1247,6 +3708,12 @@ mod tests {
             heatmap: vec![],
             markov_matrix: vec![],
             total_slots: 200,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
 
         let candidate_slots = build_candidate_start_slots(&problem);
@@ -1292,6 +3759,12 @@ mod tests {
             heatmap: vec![],
             markov_matrix: vec![],
             total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
 
         let candidate_slots = build_candidate_start_slots(&problem);
@@ -1342,6 +3815,12 @@ mod tests {
             heatmap: vec![],
             markov_matrix: vec![],
             total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
 
         let candidate_slots = build_candidate_start_slots(&problem);
@@ -1385,6 +3864,12 @@ mod tests {
             heatmap: vec![],
             markov_matrix: vec![],
             total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
 
         let candidate_slots = build_candidate_start_slots(&problem);
@@ -1443,6 +3928,12 @@ mod tests {
             heatmap: vec![],
             markov_matrix: vec![],
             total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
 
         let candidate_slots = build_candidate_start_slots(&problem);
@@ -1480,4 +3971,104 @@ mod tests {
             penalty_diff
         );
     }
+
+    #[test]
+    fn move_evaluation_matches_full_evaluation() {
+        let mut fixed = base_activity(2);
+        fixed.activity_type = ActivityType::Fixed;
+        fixed.assigned_start = Some(40);
+        let mut b = base_activity(1);
+        b.category_id = 1;
+        b.duration_slots = 4;
+
+        let problem = Problem {
+            activities: vec![base_activity(0), b, fixed],
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![2],
+            global_constraints: vec![
+                GlobalConstraint::ForbiddenZone {
+                    start: 0,
+                    end: 8,
+                    activity_id: None,
+                },
+                GlobalConstraint::ForbiddenZone {
+                    start: 60,
+                    end: 70,
+                    activity_id: Some(1),
+                },
+                GlobalConstraint::CumulativeTime {
+                    activity_id: None,
+                    category_id: Some(1),
+                    period_slots: 96,
+                    min_duration: 8,
+                    max_duration: 32,
+                    deadline_end: None,
+                },
+                GlobalConstraint::CumulativeTime {
+                    activity_id: Some(0),
+                    category_id: None,
+                    period_slots: 96,
+                    min_duration: 2,
+                    max_duration: 32,
+                    deadline_end: Some(30),
+                },
+            ],
+            heatmap: vec![(0, 20, 0.5), (1, 100, 0.4)],
+            markov_matrix: vec![(0, 1, 0.3)],
+            total_slots: 110,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let fitness = DiemFitness::new(problem, candidate_slots);
+
+        let placements = vec![(1, 62), (0, 20), (0, 4), (1, 22)];
+        let mut schedule = fitness.incremental_schedule(&placements, 50);
+        assert_eq!(
+            fitness.evaluate_schedule(&schedule),
+            fitness.evaluate_placements(&placements, 50)
+        );
+
+        let moves = [
+            (
+                PlacementMove::Remove(2),
+                vec![(1, 62), (0, 20), (1, 22)],
+                51,
+            ),
+            (
+                PlacementMove::Shift(0, 100),
+                vec![(1, 100), (0, 20), (0, 4), (1, 22)],
+                50,
+            ),
+            (
+                PlacementMove::Swap(1, 3),
+                vec![(1, 62), (0, 22), (0, 4), (1, 20)],
+                50,
+            ),
+            (
+                PlacementMove::Insert(1, 96),
+                vec![(1, 62), (0, 20), (0, 4), (1, 22), (1, 96)],
+                49,
+            ),
+        ];
+        for (placement_move, moved, idle) in &moves {
+            assert_eq!(
+                fitness.evaluate_move(&mut schedule, *placement_move),
+                fitness.evaluate_placements(moved, *idle),
+                "{:?}",
+                placement_move
+            );
+        }
+        assert_eq!(schedule.placements(), placements.as_slice());
+
+        fitness.apply_move(&mut schedule, PlacementMove::Swap(1, 3));
+        assert_eq!(
+            fitness.evaluate_schedule(&schedule),
+            fitness.evaluate_placements(&moves[2].1, 50)
+        );
+    }
 }
@@ -0,0 +1,252 @@
+use super::candidate_slots::build_candidate_start_slots;
+use super::occurrence::occurrence_bounds;
+use super::types::{Activity, ActivityId, Problem, TimeScope, TimeSlot};
+use std::collections::HashMap;
+
+/// Deterministic priority-rule schedule for instant previews and GA seeding.
+///
+/// Floating activities are placed in descending priority, each up to the number of
+/// occurrences its frequency rules ask for (at least one). Every occurrence takes the
+/// free candidate slot on the day with the fewest occurrences of that activity so far,
+/// preferring the highest heatmap probability, then the earliest slot. Fixed events,
+/// buffers, forbidden zones and the participants' earlier placements are respected:
+/// every occurrence reserves its buffers along with its own slots, and two reservations
+/// never overlap.
+pub fn greedy_schedule(problem: &Problem) -> Vec<(ActivityId, TimeSlot)> {
+    let total_slots = problem.total_slots as usize;
+    let mut candidate_start_slots = build_candidate_start_slots(problem);
+    candidate_start_slots.dedup();
+
+    // One occupancy lane per participant, or a single lane for one-person problems.
+    let lanes = problem.participants.len().max(1);
+    let mut busy = vec![vec![false; total_slots]; lanes];
+    for &act_idx in &problem.fixed_indices {
+        let activity = &problem.activities[act_idx];
+        if let Some(start) = activity.assigned_start {
+            let (from, to) = reserved(activity, start);
+            for lane in lanes_of(problem, act_idx) {
+                mark(&mut busy[lane], from, to);
+            }
+        }
+    }
+
+    let heatmap: HashMap<(ActivityId, TimeSlot), f32> = problem
+        .heatmap
+        .iter()
+        .map(|&(activity_id, slot, prob)| ((activity_id, slot), prob))
+        .collect();
+
+    let mut order = problem.floating_indices.clone();
+    order.sort_by(|&a, &b| {
+        problem.activities[b]
+            .priority
+            .total_cmp(&problem.activities[a].priority)
+    });
+
+    let mut placements = Vec::new();
+    for act_idx in order {
+        let activity = &problem.activities[act_idx];
+        let (requested, capped) = occurrence_bounds(problem, activity);
+        let wanted = requested.max(1).min(capped.unwrap_or(usize::MAX));
+        let activity_lanes = lanes_of(problem, act_idx);
+        let mut per_day = vec![0usize; problem.bucket_count(TimeScope::SameDay)];
+
+        for _ in 0..wanted {
+            let best = candidate_start_slots
+                .iter()
+                .copied()
+                .filter(|&start| {
+                    let (from, to) = reserved(activity, start);
+                    start as usize + activity.duration_slots as usize <= total_slots
+                        && !problem.in_forbidden_zone(activity.id, start, activity.duration_slots)
+                        && activity_lanes
+                            .iter()
+                            .all(|&lane| is_free(&busy[lane], from, to))
+                })
+                .min_by(|&a, &b| {
+                    let day_a = per_day[problem.bucket_of(TimeScope::SameDay, a)];
+                    let day_b = per_day[problem.bucket_of(TimeScope::SameDay, b)];
+                    let prob_a = heatmap.get(&(activity.id, a)).copied().unwrap_or(0.0);
+                    let prob_b = heatmap.get(&(activity.id, b)).copied().unwrap_or(0.0);
+                    day_a
+                        .cmp(&day_b)
+                        .then(prob_b.total_cmp(&prob_a))
+                        .then(a.cmp(&b))
+                });
+            let Some(start) = best else {
+                break;
+            };
+
+            let (from, to) = reserved(activity, start);
+            for &lane in &activity_lanes {
+                mark(&mut busy[lane], from, to);
+            }
+            per_day[problem.bucket_of(TimeScope::SameDay, start)] += 1;
+            placements.push((activity.id, start));
+        }
+    }
+
+    placements.sort_unstable_by_key(|&(_, start)| start);
+    placements
+}
+
+// Occupancy lanes an activity blocks: its participants, or every lane when unassigned.
+fn lanes_of(problem: &Problem, act_idx: usize) -> Vec<usize> {
+    let activity = &problem.activities[act_idx];
+    if problem.participants.is_empty() {
+        return vec![0];
+    }
+    problem
+        .participants
+        .iter()
+        .enumerate()
+        .filter(|(_, participant)| activity.involves(participant.id))
+        .map(|(lane, _)| lane)
+        .collect()
+}
+
+// Slots an occurrence at `start` keeps to itself: its duration plus both buffers. Two
+// reservations apart leave every pair of facing buffers room.
+fn reserved(activity: &Activity, start: TimeSlot) -> (TimeSlot, TimeSlot) {
+    let from = start.saturating_sub(activity.buffer_before);
+    let to = start
+        .saturating_add(activity.duration_slots)
+        .saturating_add(activity.buffer_after);
+    (from, to)
+}
+
+fn mark(lane: &mut [bool], start: TimeSlot, end: TimeSlot) {
+    let end = (end as usize).min(lane.len());
+    let start = (start as usize).min(end);
+    lane[start..end].fill(true);
+}
+
+fn is_free(lane: &[bool], start: TimeSlot, end: TimeSlot) -> bool {
+    let end = (end as usize).min(lane.len());
+    let start = (start as usize).min(end);
+    !lane[start..end].contains(&true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::candidate_slots::build_candidate_start_slots;
+    use crate::solver::fitness::DiemFitness;
+    use crate::solver::local_search::evaluate;
+    use crate::solver::types::{
+        ActivityType, Binding, FrequencyTarget, GlobalConstraint, UserFrequencyConstraint,
+    };
+
+    fn base_activity(id: usize, kind: ActivityType) -> Activity {
+        Activity {
+            id,
+            activity_type: kind,
+            duration_slots: 4,
+            priority: 1.0,
+            assigned_start: None,
+            category_id: 0,
+            input_bindings: Vec::<Binding>::new(),
+            output_bindings: Vec::<Binding>::new(),
+            frequency_targets: Vec::<FrequencyTarget>::new(),
+            user_frequency_constraints: Vec::<UserFrequencyConstraint>::new(),
+            occurrence_spacing: vec![],
+            buffer_before: 0,
+            buffer_after: 0,
+            location_id: None,
+            resource_requirements: vec![],
+            attention_free: false,
+            overlap_compatible_categories: vec![],
+            participant_ids: vec![],
+        }
+    }
+
+    #[test]
+    fn higher_priority_takes_contested_heatmap_slot_and_repeats_spread_over_days() {
+        let mut meeting = base_activity(0, ActivityType::Fixed);
+        meeting.assigned_start = Some(20);
+        let mut deep_work = base_activity(1, ActivityType::Floating);
+        deep_work.priority = 3.0;
+        deep_work
+            .user_frequency_constraints
+            .push(UserFrequencyConstraint {
+                scope: TimeScope::SameDay,
+                min_count: Some(1),
+                max_count: None,
+                deadline_end: None,
+                penalty_weight: 100.0,
+            });
+        let mut email = base_activity(2, ActivityType::Floating);
+        email.priority = 1.0;
+
+        let problem = Problem {
+            activities: vec![meeting, deep_work, email],
+            floating_indices: vec![1, 2],
+            fixed_indices: vec![0],
+            global_constraints: vec![GlobalConstraint::ForbiddenZone {
+                start: 0,
+                end: 8,
+                activity_id: None,
+            }],
+            heatmap: vec![
+                (1, 36, 0.9),
+                (2, 36, 0.95),
+                (2, 10, 0.4),
+                (1, 130, 0.8),
+                (1, 18, 0.99),
+            ],
+            markov_matrix: vec![],
+            total_slots: 180,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let schedule = greedy_schedule(&problem);
+
+        // Deep work at 18 would run into the meeting; it takes 36 and 130 (one per day),
+        // leaving email its second-best slot.
+        assert_eq!(schedule, vec![(2, 10), (1, 36), (1, 130)]);
+    }
+
+    #[test]
+    fn later_occurrences_stay_out_of_earlier_buffers() {
+        let mut focus = base_activity(0, ActivityType::Floating);
+        focus.priority = 2.0;
+        focus.buffer_after = 2;
+        let mut call = base_activity(1, ActivityType::Floating);
+        call.buffer_before = 1;
+
+        let problem = Problem {
+            activities: vec![focus, call],
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![],
+            global_constraints: vec![GlobalConstraint::ForbiddenZone {
+                start: 0,
+                end: 8,
+                activity_id: None,
+            }],
+            heatmap: vec![(0, 8, 0.9), (1, 12, 0.9), (1, 14, 0.8)],
+            markov_matrix: vec![],
+            total_slots: 24,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let schedule = greedy_schedule(&problem);
+
+        // Focus ends at 12 and keeps two slots after it; the call keeps one before it,
+        // so its likely start at 14 is too early.
+        assert_eq!(schedule, vec![(0, 8), (1, 15)]);
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let genes = candidate_slots.len();
+        let fitness = DiemFitness::new(problem, candidate_slots);
+        assert_eq!(evaluate(&fitness, &schedule, genes).hard_violations, 0);
+    }
+}
@@ -0,0 +1,216 @@
+use super::fitness::{DiemFitness, FitnessBreakdown, PlacementMove};
+use super::types::{ActivityId, TimeSlot};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+// Shifts tried when moving one occurrence: nearby slots, then the same time a day away.
+const MOVE_OFFSETS: [i32; 10] = [-1, 1, -2, 2, -4, 4, -8, 8, -96, 96];
+
+/// Hill-climbs on the fitness objective from `placements` with remove, move, swap and
+/// insert moves, taking the first improving move each time, until no move improves or
+/// `time_budget` runs out. Returns the improved `(activity id, start slot)` placements.
+///
+/// `genes` is the chromosome length of the encoding the placements came from; its unused
+/// genes earn the idle reward, so scores stay on the GA's scale.
+pub fn improve(
+    fitness: &DiemFitness,
+    placements: &[(ActivityId, TimeSlot)],
+    genes: usize,
+    time_budget: Duration,
+) -> Vec<(ActivityId, TimeSlot)> {
+    let deadline = Instant::now() + time_budget;
+    let problem = &fitness.problem;
+    let index_of: HashMap<ActivityId, usize> = problem
+        .activities
+        .iter()
+        .enumerate()
+        .map(|(act_idx, activity)| (activity.id, act_idx))
+        .collect();
+    let indexed: Vec<(usize, TimeSlot)> = placements
+        .iter()
+        .filter_map(|(activity_id, start)| index_of.get(activity_id).map(|&idx| (idx, *start)))
+        .collect();
+    let idle = idle_count(genes, indexed.len());
+    let mut current = fitness.incremental_schedule(&indexed, idle);
+
+    let mut start_slots = fitness.candidate_start_slots.clone();
+    start_slots.dedup();
+    let is_candidate =
+        |slot: i32| u16::try_from(slot).is_ok_and(|slot| start_slots.binary_search(&slot).is_ok());
+
+    let mut current_rank = fitness.objective.rank(&fitness.evaluate_schedule(&current));
+    let mut improved = true;
+    while improved && Instant::now() < deadline {
+        improved = false;
+        let placed = current.placements();
+        let mut neighbours = Vec::<PlacementMove>::new();
+
+        for i in 0..placed.len() {
+            neighbours.push(PlacementMove::Remove(i));
+
+            for offset in MOVE_OFFSETS {
+                let target = placed[i].1 as i32 + offset;
+                if is_candidate(target) {
+                    neighbours.push(PlacementMove::Shift(i, target as TimeSlot));
+                }
+            }
+
+            for j in i + 1..placed.len() {
+                if placed[i].0 != placed[j].0 {
+                    neighbours.push(PlacementMove::Swap(i, j));
+                }
+            }
+        }
+
+        // Inserts go to heatmap slots and right after existing occurrences.
+        for &act_idx in &problem.floating_indices {
+            let activity_id = problem.activities[act_idx].id;
+            let heatmap_slots = problem
+                .heatmap
+                .iter()
+                .filter(|&&(id, _, _)| id == activity_id)
+                .map(|&(_, slot, _)| slot);
+            let follow_slots = placed
+                .iter()
+                .map(|&(idx, start)| start.saturating_add(problem.activities[idx].duration_slots));
+            for slot in heatmap_slots.chain(follow_slots) {
+                if is_candidate(slot as i32) && !placed.contains(&(act_idx, slot)) {
+                    neighbours.push(PlacementMove::Insert(act_idx, slot));
+                }
+            }
+        }
+
+        // Moves patch the current schedule in place; each is still scored by a full sweep.
+        for neighbour in neighbours {
+            if Instant::now() >= deadline {
+                break;
+            }
+            let neighbour_rank = fitness
+                .objective
+                .rank(&fitness.evaluate_move(&mut current, neighbour));
+            if neighbour_rank > current_rank {
+                fitness.apply_move(&mut current, neighbour);
+                current_rank = neighbour_rank;
+                improved = true;
+                break;
+            }
+        }
+    }
+
+    let mut result: Vec<(ActivityId, TimeSlot)> = current
+        .placements()
+        .iter()
+        .map(|&(act_idx, start)| (problem.activities[act_idx].id, start))
+        .collect();
+    result.sort_unstable_by_key(|&(_, start)| start);
+    result
+}
+
+/// Evaluates `(activity id, start slot)` placements the way `improve` scores them.
+pub fn evaluate(
+    fitness: &DiemFitness,
+    placements: &[(ActivityId, TimeSlot)],
+    genes: usize,
+) -> FitnessBreakdown {
+    let indexed: Vec<(usize, TimeSlot)> = placements
+        .iter()
+        .filter_map(|&(activity_id, start)| {
+            fitness
+                .problem
+                .activities
+                .iter()
+                .position(|activity| activity.id == activity_id)
+                .map(|act_idx| (act_idx, start))
+        })
+        .collect();
+    evaluate_indexed(fitness, &indexed, genes)
+}
+
+pub(crate) fn evaluate_indexed(
+    fitness: &DiemFitness,
+    placements: &[(usize, TimeSlot)],
+    genes: usize,
+) -> FitnessBreakdown {
+    fitness.evaluate_placements(placements, idle_count(genes, placements.len()))
+}
+
+// Mirror the encoding's idle reward so scores match full chromosomes.
+fn idle_count(genes: usize, placed: usize) -> u32 {
+    genes.saturating_sub(placed) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::candidate_slots::build_candidate_start_slots;
+    use crate::solver::types::{
+        Activity, ActivityType, Binding, FrequencyTarget, Problem, TimeScope,
+        UserFrequencyConstraint,
+    };
+
+    fn base_activity(id: usize) -> Activity {
+        Activity {
+            id,
+            activity_type: ActivityType::Floating,
+            duration_slots: 4,
+            priority: 1.0,
+            assigned_start: None,
+            category_id: 0,
+            input_bindings: Vec::<Binding>::new(),
+            output_bindings: Vec::<Binding>::new(),
+            frequency_targets: Vec::<FrequencyTarget>::new(),
+            user_frequency_constraints: Vec::<UserFrequencyConstraint>::new(),
+            occurrence_spacing: vec![],
+            buffer_before: 0,
+            buffer_after: 0,
+            location_id: None,
+            resource_requirements: vec![],
+            attention_free: false,
+            overlap_compatible_categories: vec![],
+            participant_ids: vec![],
+        }
+    }
+
+    #[test]
+    fn repairs_overlap_and_frequency_shortfall() {
+        let a = base_activity(0);
+        let mut b = base_activity(1);
+        b.user_frequency_constraints.push(UserFrequencyConstraint {
+            scope: TimeScope::SameDay,
+            min_count: Some(2),
+            max_count: None,
+            deadline_end: None,
+            penalty_weight: 5_000.0,
+        });
+
+        let problem = Problem {
+            activities: vec![a, b],
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![],
+            global_constraints: vec![],
+            heatmap: vec![(1, 40, 0.5)],
+            markov_matrix: vec![],
+            total_slots: 90,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let fitness = DiemFitness::new(problem, candidate_slots);
+
+        // `a` and `b` overlap, and `b` is one occurrence short of its daily minimum.
+        let start = vec![(0, 10), (1, 12)];
+        let genes = fitness.candidate_start_slots.len();
+        let improved = improve(&fitness, &start, genes, Duration::from_secs(5));
+
+        let breakdown =
+            |placements: &[(ActivityId, TimeSlot)]| evaluate(&fitness, placements, genes);
+        assert!(breakdown(&start).hard_violations > 0);
+        let repaired = breakdown(&improved);
+        assert_eq!(repaired.hard_violations, 0, "got {:?}", improved);
+        assert_eq!(repaired.constraint_penalty, 0.0, "got {:?}", improved);
+    }
+}
@@ -1,25 +1,188 @@
+pub mod annealing;
+pub mod backend;
 pub mod candidate_slots;
+pub mod exact;
 pub mod fitness;
+pub mod greedy;
+pub mod local_search;
+pub mod occurrence;
+pub mod operators;
+pub mod slot_finder;
 pub mod types;
 
+use backend::GaParameters;
 use candidate_slots::build_candidate_start_slots;
-use fitness::DiemFitness;
-use genetic_algorithm::fitness::FitnessOrdering;
-use genetic_algorithm::strategy::evolve::prelude::*;
+use fitness::{DiemFitness, FitnessBreakdown, ObjectiveMode, ParetoObjectives};
+use occurrence::{occurrence_budget, OccurrenceFitness};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
 use std::error::Error;
-use types::{Problem, TimeSlot};
+use std::time::Duration;
+use types::{ActivityId, Problem, TimeSlot};
 
-// Default GA tuning for on-device solves with slot-indexed chromosomes.
-const GA_TARGET_POPULATION_SIZE: usize = 600;
-const GA_MAX_STALE_GENERATIONS: usize = 120;
-const GA_MULTI_GENE_MUTATION_COUNT: usize = 2;
-const GA_MUTATION_PROBABILITY: f32 = 0.4;
+// Nodes the exact search may visit before falling back to the backend.
+const EXACT_NODE_LIMIT: usize = 200_000;
+
+// Soft-score points between an objective's worst and best anchor value in the balanced
+// Pareto run; well above the tenth-of-a-point tier resolution.
+const PARETO_NORMALIZED_SPAN: f32 = 1_000.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SolverConfig {
+    pub max_generations: usize,
+    /// Wall-clock budget of the whole solve, local search included; 0 leaves the backend to
+    /// `max_generations` and staleness.
+    #[serde(default)]
+    pub time_limit_ms: u64,
+    /// Number of alternative schedules to return; the first is always the best found.
+    /// Fewer come back when the search found fewer far enough apart (see `min_distance`).
+    #[serde(default = "default_alternatives")]
+    pub alternatives: usize,
+    /// Minimum number of moved occurrences between any two returned schedules.
+    #[serde(default = "default_min_distance")]
+    pub min_distance: usize,
+    #[serde(default)]
+    pub objective: ObjectiveMode,
+    #[serde(default)]
+    pub encoding: Encoding,
+    #[serde(default)]
+    pub backend: Backend,
+    #[serde(default)]
+    pub operators: GeneticOperators,
+    /// Populations evolved on separate threads by the genetic backend, with periodic
+    /// migration of each island's best; 1 runs a single population on this thread. Capped
+    /// at the available cores, and for a derived population at one island per 60
+    /// chromosomes; a `ga_parameters` population too small for the islands is rejected.
+    #[serde(default = "default_islands")]
+    pub islands: usize,
+    /// Largest total occurrence budget (explicit maxima, else the requested counts or one
+    /// per day) for which the exact search runs before the backend; 0 disables it. Only
+    /// used for single-schedule lexicographic solves.
+    #[serde(default = "default_exact_max_occurrences")]
+    pub exact_max_occurrences: usize,
+    /// Time budget for the local-search repair pass over the returned schedules; 0 skips it.
+    /// Taken out of `time_limit_ms` when that is set, and then at most half of it.
+    #[serde(default = "default_local_search_ms")]
+    pub local_search_ms: u64,
+    /// Fixed GA tuning; `None` derives it from the problem size and time budget. An
+    /// override that fails `GaParameters::validate` makes the solve return an error.
+    #[serde(default)]
+    pub ga_parameters: Option<GaParameters>,
+}
+
+/// Metaheuristic that searches the chromosome space; see `backend::SolverBackend`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Backend {
+    /// `genetic_algorithm` Evolve with a large population.
+    #[default]
+    Genetic,
+    /// Single-chromosome simulated annealing; far fewer evaluations per step.
+    Annealing,
+}
+
+/// Mutation and crossover used by the genetic backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GeneticOperators {
+    /// `MutateMultiGene` and `CrossoverUniform`.
+    #[default]
+    Generic,
+    /// Duration-aware place/shift/swap mutation and day-block crossover
+    /// (`operators::MutateSchedule`, `operators::CrossoverDayBlock`) where the encoding
+    /// supports them; slot-indexed genes do, occurrence-indexed genes keep the generic ones.
+    ScheduleAware,
+}
+
+/// Chromosome layout used by the GA.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Encoding {
+    /// One gene per candidate start slot; the allele picks a floating activity.
+    #[default]
+    SlotIndexed,
+    /// One gene per potential occurrence; the allele picks a start slot.
+    OccurrenceIndexed,
+}
+
+fn default_alternatives() -> usize {
+    1
+}
+
+fn default_min_distance() -> usize {
+    1
+}
+
+fn default_islands() -> usize {
+    1
+}
+
+fn default_exact_max_occurrences() -> usize {
+    8
+}
+
+fn default_local_search_ms() -> u64 {
+    100
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            max_generations: 1_000,
+            time_limit_ms: 0,
+            alternatives: default_alternatives(),
+            min_distance: default_min_distance(),
+            objective: ObjectiveMode::default(),
+            encoding: Encoding::default(),
+            backend: Backend::default(),
+            operators: GeneticOperators::default(),
+            islands: default_islands(),
+            exact_max_occurrences: default_exact_max_occurrences(),
+            local_search_ms: default_local_search_ms(),
+            ga_parameters: None,
+        }
+    }
+}
+
+/// One decoded schedule with its fitness.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleCandidate {
+    pub placements: Vec<(ActivityId, TimeSlot)>,
+    pub fitness: isize,
+    pub breakdown: FitnessBreakdown,
+    pub objectives: ParetoObjectives,
+    /// Set when the exact search proved no schedule scores higher among those with clear
+    /// occurrences within each activity's occurrence budget. Not a global optimum: more
+    /// occurrences or conflicting ones were not searched.
+    #[serde(default)]
+    pub proven_optimal: bool,
+}
 
 pub fn solve(
     problem: Problem,
     max_generations: usize,
-    _time_limit_ms: u64,
-) -> Result<Vec<(types::ActivityId, TimeSlot)>, Box<dyn Error>> {
+    time_limit_ms: u64,
+) -> Result<Vec<(ActivityId, TimeSlot)>, Box<dyn Error>> {
+    let config = SolverConfig {
+        max_generations,
+        time_limit_ms,
+        ..SolverConfig::default()
+    };
+    let mut candidates = solve_with_config(problem, &config)?;
+    Ok(if candidates.is_empty() {
+        vec![]
+    } else {
+        candidates.swap_remove(0).placements
+    })
+}
+
+/// Runs the configured backend and returns up to `config.alternatives` schedules, best first, each at
+/// least `config.min_distance` moved occurrences away from every other one. A converged search
+/// can yield fewer than `config.alternatives`.
+pub fn solve_with_config(
+    problem: Problem,
+    config: &SolverConfig,
+) -> Result<Vec<ScheduleCandidate>, Box<dyn Error>> {
+    if let Some(parameters) = &config.ga_parameters {
+        parameters.validate(config.islands)?;
+    }
     let floating_count = problem.floating_indices.len();
     if floating_count == 0 {
         return Ok(vec![]);
@@ -29,66 +192,282 @@ pub fn solve(
     if candidate_start_slots.is_empty() {
         return Ok(vec![]);
     }
-    let no_activity_allele = u16::try_from(floating_count)
-        .map_err(|_| format!("Floating activity count exceeds u16: {}", floating_count))?;
-
-    let genotype = RangeGenotype::builder()
-        .with_genes_size(candidate_start_slots.len())
-        .with_allele_range(0..=no_activity_allele)
-        .build()
-        .map_err(|e| format!("Genotype build error: {:?}", e))?;
-
-    let fitness = DiemFitness::new(problem.clone(), candidate_start_slots.clone());
-
-    let evolve = Evolve::builder()
-        .with_genotype(genotype)
-        .with_fitness(fitness)
-        .with_fitness_ordering(FitnessOrdering::Maximize)
-        .with_target_population_size(GA_TARGET_POPULATION_SIZE)
-        .with_max_stale_generations(GA_MAX_STALE_GENERATIONS)
-        .with_max_generations(max_generations)
-        .with_select(SelectTournament::new(0.8, 0.1, 4))
-        .with_crossover(CrossoverUniform::new(0.5, 0.5))
-        .with_mutate(MutateMultiGene::new(
-            GA_MULTI_GENE_MUTATION_COUNT,
-            GA_MUTATION_PROBABILITY,
-        ))
-        .call()
-        .map_err(|e| format!("Evolve strategy failed: {:?}", e))?;
-
-    if let Some(best_chromosome) = evolve.best_chromosome() {
-        println!(
-            "-> GA solver finished. Best fitness score: {:?}",
-            best_chromosome.fitness_score()
-        );
+    let mut fitness = DiemFitness::new(problem, candidate_start_slots);
+    fitness.objective = config.objective;
+
+    if let Some(candidate) = try_exact(&fitness, config) {
+        return Ok(vec![candidate]);
+    }
 
-        let mut result = Vec::new();
-        for (gene_idx, &activity_choice) in best_chromosome.genes.iter().enumerate() {
-            if activity_choice == no_activity_allele {
-                continue;
+    let (search_ms, local_search_ms) = time_budgets(config);
+    let search_config = SolverConfig {
+        time_limit_ms: search_ms,
+        ..config.clone()
+    };
+
+    // Chromosome length of the encoding, whose unused genes set the idle reward.
+    let (genes_size, mut scored): (usize, Vec<ScheduleCandidate>) = match config.encoding {
+        Encoding::SlotIndexed => {
+            let no_activity_allele = u16::try_from(floating_count)
+                .map_err(|_| format!("Floating activity count exceeds u16: {}", floating_count))?;
+            let genes_size = fitness.candidate_start_slots.len();
+            let pool = backend::search(
+                genes_size,
+                no_activity_allele,
+                fitness.clone(),
+                &search_config,
+            )?;
+            let scored = pool
+                .iter()
+                .map(|genes| {
+                    schedule_candidate(fitness.decode(genes), fitness.evaluate(genes), config)
+                })
+                .collect();
+            (genes_size, scored)
+        }
+        Encoding::OccurrenceIndexed => {
+            let fitness = OccurrenceFitness::new(fitness.clone());
+            if fitness.owners.is_empty() {
+                return Ok(vec![]);
             }
-            let floating_choice = activity_choice as usize;
-            if floating_choice >= floating_count || gene_idx >= candidate_start_slots.len() {
-                continue;
+            let pool = backend::search(
+                fitness.owners.len(),
+                fitness.unused_allele,
+                fitness.clone(),
+                &search_config,
+            )?;
+            let scored = pool
+                .iter()
+                .map(|genes| {
+                    schedule_candidate(fitness.decode(genes), fitness.evaluate(genes), config)
+                })
+                .collect();
+            (fitness.owners.len(), scored)
+        }
+    };
+    // Stable sort keeps the GA's best chromosome ahead of equally ranked copies.
+    scored.sort_by_key(|candidate| Reverse(config.objective.rank(&candidate.breakdown)));
+
+    let min_distance = config.min_distance.max(1);
+    let mut selected = Vec::<ScheduleCandidate>::new();
+    for candidate in scored {
+        if selected.len() >= config.alternatives.max(1) {
+            break;
+        }
+        if selected.iter().all(|chosen| {
+            moved_occurrences(&chosen.placements, &candidate.placements) >= min_distance
+        }) {
+            selected.push(candidate);
+        }
+    }
+
+    if local_search_ms > 0 && !selected.is_empty() {
+        // The GA stops on staleness; hill-climb each schedule to a local optimum.
+        let budget = Duration::from_millis(local_search_ms) / selected.len() as u32;
+        let mut repaired = Vec::<ScheduleCandidate>::with_capacity(selected.len());
+        for candidate in selected {
+            let placements =
+                local_search::improve(&fitness, &candidate.placements, genes_size, budget);
+            let breakdown = local_search::evaluate(&fitness, &placements, genes_size);
+            // Keep the GA's schedule when repair would collapse it onto another alternative.
+            let distinct = repaired
+                .iter()
+                .all(|kept| moved_occurrences(&kept.placements, &placements) >= min_distance);
+            if distinct
+                && config.objective.rank(&breakdown) > config.objective.rank(&candidate.breakdown)
+            {
+                repaired.push(schedule_candidate(placements, breakdown, config));
+            } else {
+                repaired.push(candidate);
             }
+        }
+        repaired.sort_by_key(|candidate| Reverse(config.objective.rank(&candidate.breakdown)));
+        selected = repaired;
+    }
+    Ok(selected)
+}
+
+// Milliseconds for the backend search and for local search. Under a time limit, local
+// search takes its budget out of it and leaves the backend at least half.
+fn time_budgets(config: &SolverConfig) -> (u64, u64) {
+    if config.time_limit_ms == 0 {
+        return (0, config.local_search_ms);
+    }
+    let local_search_ms = config.local_search_ms.min(config.time_limit_ms / 2);
+    (config.time_limit_ms - local_search_ms, local_search_ms)
+}
 
-            let act_idx = problem.floating_indices[floating_choice];
-            let activity_id = problem.activities[act_idx].id;
-            let start_time = candidate_start_slots[gene_idx];
-            result.push((activity_id, start_time));
+// Exact search for small single-schedule lexicographic solves; `None` when the problem
+// is over the configured size, the search hits its node limit or its best schedule
+// violates a hard rule.
+fn try_exact(fitness: &DiemFitness, config: &SolverConfig) -> Option<ScheduleCandidate> {
+    if config.alternatives > 1 || config.objective != ObjectiveMode::Lexicographic {
+        return None;
+    }
+    let problem = &fitness.problem;
+    let total_budget: usize = problem
+        .floating_indices
+        .iter()
+        .map(|&act_idx| occurrence_budget(problem, &problem.activities[act_idx]))
+        .sum();
+    if total_budget > config.exact_max_occurrences {
+        return None;
+    }
+    let solution = exact::solve_exact(fitness, EXACT_NODE_LIMIT)?;
+    // The search only places clear occurrences within each activity's budget; when that
+    // still breaks a hard rule, leave the problem to the backend.
+    if solution.breakdown.hard_violations > 0 {
+        return None;
+    }
+    let mut candidate = schedule_candidate(solution.placements, solution.breakdown, config);
+    candidate.proven_optimal = true;
+    Some(candidate)
+}
+
+fn schedule_candidate(
+    placements: Vec<(ActivityId, TimeSlot)>,
+    breakdown: FitnessBreakdown,
+    config: &SolverConfig,
+) -> ScheduleCandidate {
+    ScheduleCandidate {
+        placements,
+        fitness: config.objective.score(&breakdown),
+        objectives: breakdown.objectives(),
+        breakdown,
+        proven_optimal: false,
+    }
+}
+
+/// Sweeps the trade-off between objectives and returns the non-dominated schedules among
+/// those with the fewest hard violations, most productive first. One anchor run per
+/// objective finds its best and worst values; a last run weighs every objective by that
+/// range, so objectives on different scales (slots, rewards) count equally. The five
+/// runs share `max_generations`, `time_limit_ms` and `local_search_ms`.
+/// `config.alternatives` sets how many schedules each run contributes to the archive.
+pub fn solve_pareto(
+    problem: Problem,
+    config: &SolverConfig,
+) -> Result<Vec<ScheduleCandidate>, Box<dyn Error>> {
+    let anchors = [
+        ParetoObjectives {
+            productivity: 1.0,
+            ..ParetoObjectives::default()
+        },
+        ParetoObjectives {
+            habit_fit: 1.0,
+            ..ParetoObjectives::default()
+        },
+        ParetoObjectives {
+            adherence: 1.0,
+            ..ParetoObjectives::default()
+        },
+        ParetoObjectives {
+            free_time: 1.0,
+            ..ParetoObjectives::default()
+        },
+    ];
+    let runs = anchors.len() as u64 + 1;
+    let run_config = |weights: ParetoObjectives| SolverConfig {
+        objective: ObjectiveMode::Tradeoff(weights),
+        max_generations: config.max_generations.div_ceil(runs as usize),
+        time_limit_ms: config.time_limit_ms.div_ceil(runs),
+        local_search_ms: config.local_search_ms.div_ceil(runs),
+        ..config.clone()
+    };
+
+    let mut found = Vec::<ScheduleCandidate>::new();
+    for weights in anchors {
+        found.extend(solve_with_config(problem.clone(), &run_config(weights))?);
+    }
+    let mut ideal = [f32::MIN; 4];
+    let mut nadir = [f32::MAX; 4];
+    for candidate in &found {
+        for (k, value) in candidate.objectives.values().into_iter().enumerate() {
+            ideal[k] = ideal[k].max(value);
+            nadir[k] = nadir[k].min(value);
         }
-        result.sort_unstable_by_key(|(_, start_time)| *start_time);
-        Ok(result)
-    } else {
-        Ok(vec![])
     }
+
+    // Each objective spans `PARETO_NORMALIZED_SPAN` from its worst to its best anchor value;
+    // one that did not vary gets no weight.
+    let span = |k: usize| {
+        let range = ideal[k] - nadir[k];
+        if range > f32::EPSILON {
+            PARETO_NORMALIZED_SPAN / range
+        } else {
+            0.0
+        }
+    };
+    let balanced = ParetoObjectives {
+        productivity: span(0),
+        habit_fit: span(1),
+        adherence: span(2),
+        free_time: span(3),
+    };
+    found.extend(solve_with_config(problem, &run_config(balanced))?);
+
+    let mut archive = Vec::<ScheduleCandidate>::new();
+    for mut candidate in found {
+        candidate.fitness = config.objective.score(&candidate.breakdown);
+        insert_non_dominated(&mut archive, candidate);
+    }
+    archive.sort_by(|a, b| {
+        b.objectives
+            .productivity
+            .total_cmp(&a.objectives.productivity)
+    });
+    Ok(archive)
+}
+
+fn insert_non_dominated(archive: &mut Vec<ScheduleCandidate>, candidate: ScheduleCandidate) {
+    let fewest_hard = archive
+        .iter()
+        .map(|kept| kept.breakdown.hard_violations)
+        .min()
+        .unwrap_or(u32::MAX);
+    let hard = candidate.breakdown.hard_violations;
+    if hard > fewest_hard {
+        return;
+    }
+    if hard < fewest_hard {
+        archive.clear();
+    }
+    if archive.iter().any(|kept| {
+        kept.placements == candidate.placements || kept.objectives.dominates(&candidate.objectives)
+    }) {
+        return;
+    }
+    archive.retain(|kept| !candidate.objectives.dominates(&kept.objectives));
+    archive.push(candidate);
+}
+
+/// Number of occurrences that must move to turn one schedule into the other.
+pub fn moved_occurrences(a: &[(ActivityId, TimeSlot)], b: &[(ActivityId, TimeSlot)]) -> usize {
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort_unstable();
+    b.sort_unstable();
+    let (mut i, mut j, mut shared) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                shared += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    a.len().max(b.len()) - shared
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::solver::types::{
-        Activity, ActivityType, Binding, FrequencyTarget, GlobalConstraint, UserFrequencyConstraint,
+        Activity, ActivityType, Binding, FrequencyTarget, GlobalConstraint, TimeScope,
+        UserFrequencyConstraint,
     };
 
     fn base_activity(id: usize, kind: ActivityType) -> Activity {
@@ -103,6 +482,47 @@ mod tests {
             output_bindings: Vec::<Binding>::new(),
             frequency_targets: Vec::<FrequencyTarget>::new(),
             user_frequency_constraints: Vec::<UserFrequencyConstraint>::new(),
+            occurrence_spacing: vec![],
+            buffer_before: 0,
+            buffer_after: 0,
+            location_id: None,
+            resource_requirements: vec![],
+            attention_free: false,
+            overlap_compatible_categories: vec![],
+            participant_ids: vec![],
+        }
+    }
+
+    fn at_most_once_a_day() -> UserFrequencyConstraint {
+        UserFrequencyConstraint {
+            scope: TimeScope::SameDay,
+            min_count: None,
+            max_count: Some(1),
+            deadline_end: None,
+            penalty_weight: 100.0,
+        }
+    }
+
+    // One day with two floating four-slot activities, each with one likely start.
+    fn test_problem() -> Problem {
+        let mut workout = base_activity(0, ActivityType::Floating);
+        workout.duration_slots = 4;
+        let mut reading = base_activity(1, ActivityType::Floating);
+        reading.duration_slots = 4;
+        Problem {
+            activities: vec![workout, reading],
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![],
+            global_constraints: vec![],
+            heatmap: vec![(0, 8, 0.9), (1, 40, 0.9)],
+            markov_matrix: vec![],
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         }
     }
 
@@ -122,11 +542,27 @@ mod tests {
             heatmap: vec![(0, 2, 6.0), (0, 4, 6.0), (0, 6, 6.0)],
             markov_matrix: vec![],
             total_slots: 10,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
         problem.activities[0].priority = 2.0;
         problem.activities[1].priority = 0.0;
 
-        let result = solve(problem, 180, 200).expect("solver should return duplicate activity IDs");
+        // The exact search stops at one occurrence a day; repeats are the GA's to find.
+        let config = SolverConfig {
+            max_generations: 180,
+            time_limit_ms: 200,
+            exact_max_occurrences: 0,
+            ..SolverConfig::default()
+        };
+        let result = solve_with_config(problem, &config)
+            .expect("solver should return duplicate activity IDs")
+            .swap_remove(0)
+            .placements;
         let count_a = result.iter().filter(|(id, _)| *id == 0).count();
 
         assert!(
@@ -150,6 +586,12 @@ mod tests {
             heatmap: vec![],
             markov_matrix: vec![],
             total_slots: 1,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
 
         let result = solve(problem, 50, 100).expect("solver should succeed");
@@ -182,6 +624,12 @@ mod tests {
             heatmap: vec![],
             markov_matrix: vec![],
             total_slots: 192,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
         };
 
         let result = solve(problem, 220, 300).expect("solver should satisfy daily minimum");
@@ -200,4 +648,322 @@ mod tests {
             result
         );
     }
+
+    #[test]
+    fn alternatives_are_distinct_and_ranked() {
+        let activity = base_activity(0, ActivityType::Floating);
+        let problem = Problem {
+            activities: vec![activity],
+            floating_indices: vec![0],
+            fixed_indices: vec![],
+            global_constraints: vec![],
+            heatmap: vec![(0, 2, 0.9), (0, 6, 0.6), (0, 10, 0.3)],
+            markov_matrix: vec![],
+            total_slots: 12,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+        let config = SolverConfig {
+            max_generations: 100,
+            alternatives: 3,
+            min_distance: 1,
+            ..SolverConfig::default()
+        };
+
+        let candidates = solve_with_config(problem, &config).expect("solver should succeed");
+
+        assert_eq!(candidates.len(), 3);
+        for (i, a) in candidates.iter().enumerate() {
+            assert_eq!(a.fitness, a.breakdown.lexicographic_score());
+            for b in &candidates[i + 1..] {
+                assert!(a.fitness >= b.fitness);
+                assert!(moved_occurrences(&a.placements, &b.placements) >= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn pareto_front_trades_productivity_for_free_time() {
+        let mut activity = base_activity(0, ActivityType::Floating);
+        activity.duration_slots = 4;
+        let problem = Problem {
+            activities: vec![activity],
+            floating_indices: vec![0],
+            fixed_indices: vec![],
+            global_constraints: vec![],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 16,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+        let config = SolverConfig {
+            max_generations: 60,
+            alternatives: 4,
+            ..SolverConfig::default()
+        };
+
+        let front = solve_pareto(problem, &config).expect("solver should succeed");
+
+        assert!(front.len() >= 2, "expected a trade-off, got {:?}", front);
+        for (i, a) in front.iter().enumerate() {
+            assert_eq!(a.breakdown.hard_violations, 0);
+            for b in &front[i + 1..] {
+                assert!(!a.objectives.dominates(&b.objectives));
+                assert!(!b.objectives.dominates(&a.objectives));
+                assert!(a.objectives.productivity >= b.objectives.productivity);
+            }
+        }
+    }
+
+    #[test]
+    fn occurrence_encoding_pareto_front_is_non_dominated() {
+        let config = SolverConfig {
+            max_generations: 150,
+            alternatives: 3,
+            encoding: Encoding::OccurrenceIndexed,
+            exact_max_occurrences: 0,
+            ..SolverConfig::default()
+        };
+
+        let front = solve_pareto(test_problem(), &config).expect("solver should succeed");
+
+        assert!(!front.is_empty());
+        for (i, a) in front.iter().enumerate() {
+            assert_eq!(a.breakdown.hard_violations, 0, "got {:?}", a);
+            for b in &front[i + 1..] {
+                assert!(
+                    !a.objectives.dominates(&b.objectives),
+                    "{:?} over {:?}",
+                    a,
+                    b
+                );
+                assert!(
+                    !b.objectives.dominates(&a.objectives),
+                    "{:?} over {:?}",
+                    b,
+                    a
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn repaired_occurrence_schedules_keep_the_encoding_scale() {
+        let mut problem = test_problem();
+        for activity in &mut problem.activities {
+            activity
+                .user_frequency_constraints
+                .push(at_most_once_a_day());
+        }
+        let config = SolverConfig {
+            max_generations: 20,
+            encoding: Encoding::OccurrenceIndexed,
+            exact_max_occurrences: 0,
+            alternatives: 2,
+            ..SolverConfig::default()
+        };
+
+        let candidates =
+            solve_with_config(problem.clone(), &config).expect("solver should succeed");
+
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let occurrences = OccurrenceFitness::new(DiemFitness::new(problem, candidate_slots));
+        for candidate in &candidates {
+            let genes = occurrences.encode(&candidate.placements);
+            assert_eq!(occurrences.decode(&genes), candidate.placements);
+            assert_eq!(occurrences.evaluate(&genes), candidate.breakdown);
+        }
+    }
+
+    #[test]
+    fn generic_operators_are_the_default() {
+        let problem = test_problem();
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let fitness = DiemFitness::new(problem.clone(), candidate_slots);
+        let schedule_aware = SolverConfig {
+            max_generations: 150,
+            operators: GeneticOperators::ScheduleAware,
+            exact_max_occurrences: 0,
+            local_search_ms: 0,
+            ..SolverConfig::default()
+        };
+
+        assert_eq!(SolverConfig::default().operators, GeneticOperators::Generic);
+        assert!(backend::schedule_layout(&fitness, &SolverConfig::default()).is_none());
+        assert!(backend::schedule_layout(&fitness, &schedule_aware).is_some());
+        // Occurrence-indexed genes keep the generic operators even when asked otherwise.
+        let occurrences = OccurrenceFitness::new(fitness);
+        assert!(backend::schedule_layout(&occurrences, &schedule_aware).is_none());
+
+        let candidates =
+            solve_with_config(problem, &schedule_aware).expect("solver should succeed");
+        let best = &candidates[0];
+        assert!(
+            best.placements.contains(&(0, 8)) && best.placements.contains(&(1, 40)),
+            "got {:?}",
+            best.placements
+        );
+    }
+
+    #[test]
+    fn annealing_improves_on_the_greedy_seed() {
+        // Greedy ignores transitions and puts reading at the first free slot; reading
+        // right after the workout earns the Markov reward.
+        let mut problem = test_problem();
+        problem.total_slots = 16;
+        problem.heatmap = vec![(0, 8, 0.9)];
+        problem.markov_matrix = vec![(0, 1, 1.0)];
+        for activity in &mut problem.activities {
+            activity
+                .user_frequency_constraints
+                .push(at_most_once_a_day());
+        }
+        let config = SolverConfig {
+            max_generations: 20,
+            backend: Backend::Annealing,
+            exact_max_occurrences: 0,
+            local_search_ms: 0,
+            ..SolverConfig::default()
+        };
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let fitness = DiemFitness::new(problem.clone(), candidate_slots);
+        let genes = fitness.candidate_start_slots.len();
+        let seed = local_search::evaluate(&fitness, &greedy::greedy_schedule(&problem), genes);
+
+        let candidates = solve_with_config(problem, &config).expect("solver should succeed");
+
+        let best = &candidates[0];
+        assert!(
+            best.breakdown.lexicographic_rank() > seed.lexicographic_rank(),
+            "got {:?}",
+            best
+        );
+        assert!(
+            best.breakdown.habit_reward > seed.habit_reward,
+            "got {:?}",
+            best
+        );
+    }
+
+    #[test]
+    fn island_model_returns_alternatives_from_its_populations() {
+        let config = SolverConfig {
+            max_generations: 100,
+            islands: 3,
+            alternatives: 2,
+            exact_max_occurrences: 0,
+            local_search_ms: 0,
+            // Room for three islands of the minimum size.
+            ga_parameters: Some(GaParameters {
+                population_size: 180,
+                ..GaParameters::scaled(96, 2, 0)
+            }),
+            ..SolverConfig::default()
+        };
+
+        let candidates = solve_with_config(test_problem(), &config).expect("solver should succeed");
+
+        let too_small = SolverConfig {
+            ga_parameters: Some(GaParameters {
+                population_size: 100,
+                ..GaParameters::scaled(96, 2, 0)
+            }),
+            ..config.clone()
+        };
+        assert!(solve_with_config(test_problem(), &too_small).is_err());
+
+        // The overall best across islands, then an alternative from their populations.
+        assert_eq!(candidates.len(), 2);
+        assert!(moved_occurrences(&candidates[0].placements, &candidates[1].placements) >= 1);
+        let best = &candidates[0];
+        assert!(
+            best.placements.contains(&(0, 8)) && best.placements.contains(&(1, 40)),
+            "got {:?}",
+            best.placements
+        );
+    }
+
+    #[test]
+    fn local_search_comes_out_of_the_time_limit() {
+        let legacy = SolverConfig {
+            time_limit_ms: 500,
+            ..SolverConfig::default()
+        };
+        assert_eq!(time_budgets(&legacy), (400, 100));
+        let short = SolverConfig {
+            time_limit_ms: 50,
+            ..SolverConfig::default()
+        };
+        assert_eq!(time_budgets(&short), (25, 25));
+        let unlimited = SolverConfig::default();
+        assert_eq!(time_budgets(&unlimited), (0, 100));
+    }
+
+    #[test]
+    fn small_single_day_problem_takes_the_exact_path() {
+        let mut problem = test_problem();
+        let mut call = base_activity(2, ActivityType::Floating);
+        call.duration_slots = 2;
+        problem.activities.push(call);
+        problem.floating_indices.push(2);
+
+        let candidates =
+            solve_with_config(problem, &SolverConfig::default()).expect("solver should succeed");
+
+        assert!(candidates[0].proven_optimal, "got {:?}", candidates[0]);
+        assert_eq!(candidates[0].breakdown.hard_violations, 0);
+    }
+
+    #[test]
+    fn time_limit_stops_the_genetic_search() {
+        let mut activity = base_activity(0, ActivityType::Floating);
+        activity.duration_slots = 4;
+        let problem = Problem {
+            activities: vec![activity],
+            floating_indices: vec![0],
+            fixed_indices: vec![],
+            global_constraints: vec![],
+            heatmap: vec![(0, 8, 0.9)],
+            markov_matrix: vec![],
+            total_slots: 96,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+        let config = SolverConfig {
+            max_generations: usize::MAX,
+            time_limit_ms: 200,
+            exact_max_occurrences: 0,
+            local_search_ms: 0,
+            ga_parameters: Some(GaParameters {
+                population_size: 50,
+                max_stale_generations: usize::MAX,
+                mutation_count: 2,
+                mutation_probability: 0.5,
+            }),
+            ..SolverConfig::default()
+        };
+
+        let started = std::time::Instant::now();
+        let candidates = solve_with_config(problem, &config).expect("solver should succeed");
+
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "took {:?}",
+            started.elapsed()
+        );
+        assert_eq!(candidates[0].breakdown.hard_violations, 0);
+    }
 }
@@ -0,0 +1,275 @@
+use super::backend::SeededFitness;
+use super::fitness::{DiemFitness, FitnessBreakdown};
+use super::greedy::greedy_schedule;
+use super::types::{Activity, ActivityId, Problem, TimeScope, TimeSlot};
+use genetic_algorithm::chromosome::Chromosome;
+use genetic_algorithm::fitness::{Fitness, FitnessValue};
+use genetic_algorithm::genotype::RangeGenotype;
+use genetic_algorithm::strategy::{StrategyAction, StrategyConfig, StrategyState};
+use std::cell::RefCell;
+use std::time::Instant;
+use thread_local::ThreadLocal;
+
+// Upper bound on occurrence genes reserved for a single floating activity.
+const MAX_OCCURRENCES_PER_ACTIVITY: usize = 64;
+
+/// Occurrence-indexed encoding: one gene per potential occurrence of a floating activity,
+/// whose allele picks a start slot from `start_slots`; `unused_allele` leaves it unscheduled.
+///
+/// Gene count scales with the occurrences the problem asks for instead of the horizon
+/// length, so month-long horizons do not carry thousands of idle genes.
+#[derive(Clone, Debug)]
+pub struct OccurrenceFitness {
+    pub inner: DiemFitness,
+    /// Floating choice (index into `floating_indices`) owning each gene.
+    pub owners: Vec<usize>,
+    /// Distinct candidate start slots.
+    pub start_slots: Vec<TimeSlot>,
+    pub unused_allele: u16,
+}
+
+impl OccurrenceFitness {
+    pub fn new(inner: DiemFitness) -> Self {
+        let mut start_slots = inner.candidate_start_slots.clone();
+        start_slots.dedup();
+        let unused_allele = u16::try_from(start_slots.len()).unwrap_or(u16::MAX);
+
+        let problem = &inner.problem;
+        let mut owners = Vec::new();
+        for (floating_choice, &act_idx) in problem.floating_indices.iter().enumerate() {
+            let budget = occurrence_budget(problem, &problem.activities[act_idx]);
+            owners.extend(std::iter::repeat_n(floating_choice, budget));
+        }
+
+        Self {
+            inner,
+            owners,
+            start_slots,
+            unused_allele,
+        }
+    }
+
+    // Genes as `(activity index, start slot)` pairs plus the count of unused genes.
+    fn gene_placements(&self, genes: &[u16]) -> (Vec<(usize, TimeSlot)>, u32) {
+        let mut placements = Vec::with_capacity(genes.len());
+        let mut unused_count = 0u32;
+        for (&owner, &slot_choice) in self.owners.iter().zip(genes) {
+            match self.start_slots.get(slot_choice as usize) {
+                Some(&start_time) => {
+                    placements.push((self.inner.problem.floating_indices[owner], start_time))
+                }
+                None => unused_count += 1,
+            }
+        }
+        (placements, unused_count)
+    }
+
+    pub fn evaluate(&self, genes: &[u16]) -> FitnessBreakdown {
+        let (placements, unused_count) = self.gene_placements(genes);
+        self.inner.evaluate_placements(&placements, unused_count)
+    }
+
+    /// Encodes placements into the genes of their activities' occurrences; placements
+    /// beyond an activity's budget or off the candidate slots are dropped.
+    pub fn encode(&self, placements: &[(ActivityId, TimeSlot)]) -> Vec<u16> {
+        let problem = &self.inner.problem;
+        let mut genes = vec![self.unused_allele; self.owners.len()];
+        for &(activity_id, start_time) in placements {
+            let Ok(slot_choice) = self.start_slots.binary_search(&start_time) else {
+                continue;
+            };
+            let free_gene = (0..self.owners.len()).find(|&idx| {
+                genes[idx] == self.unused_allele
+                    && problem.activities[problem.floating_indices[self.owners[idx]]].id
+                        == activity_id
+            });
+            if let Some(idx) = free_gene {
+                genes[idx] = slot_choice as u16;
+            }
+        }
+        genes
+    }
+
+    /// Decodes genes into `(activity id, start slot)` placements, sorted by start.
+    pub fn decode(&self, genes: &[u16]) -> Vec<(ActivityId, TimeSlot)> {
+        let (placements, _) = self.gene_placements(genes);
+        let mut placements: Vec<(ActivityId, TimeSlot)> = placements
+            .into_iter()
+            .map(|(act_idx, start_time)| (self.inner.problem.activities[act_idx].id, start_time))
+            .collect();
+        placements.sort_unstable_by_key(|&(_, start_time)| start_time);
+        placements
+    }
+}
+
+impl Fitness for OccurrenceFitness {
+    type Genotype = RangeGenotype<u16>;
+
+    fn call_for_state_population<S: StrategyState<Self::Genotype>, C: StrategyConfig>(
+        &mut self,
+        genotype: &Self::Genotype,
+        state: &mut S,
+        config: &C,
+        thread_local: Option<&ThreadLocal<RefCell<Self>>>,
+    ) {
+        if state.current_generation() == 0 {
+            let population = state.population_as_mut();
+            if let Some(chromosome) = population.chromosomes.first_mut() {
+                chromosome.genes = self.seed_genes();
+                chromosome.reset_metadata(false);
+            }
+        }
+
+        let now = Instant::now();
+        self.call_for_population(
+            state.population_as_mut(),
+            genotype,
+            thread_local,
+            config.fitness_cache(),
+        );
+        state.add_duration(StrategyAction::Fitness, now.elapsed());
+    }
+
+    fn calculate_for_chromosome(
+        &mut self,
+        chromosome: &Chromosome<u16>,
+        _genotype: &Self::Genotype,
+    ) -> Option<FitnessValue> {
+        Some(
+            self.inner
+                .objective
+                .score(&self.evaluate(&chromosome.genes)),
+        )
+    }
+}
+
+impl SeededFitness for OccurrenceFitness {
+    fn seed_genes(&self) -> Vec<u16> {
+        self.encode(&greedy_schedule(&self.inner.problem))
+    }
+
+    fn floating_count(&self) -> usize {
+        self.inner.problem.floating_indices.len()
+    }
+}
+
+/// Occurrences the frequency rules ask for over the horizon (largest minimum or soft
+/// target) and the tightest explicit maximum, if any.
+pub(crate) fn occurrence_bounds(problem: &Problem, activity: &Activity) -> (usize, Option<usize>) {
+    let over_horizon = |scope: TimeScope, count: u16| count as usize * problem.bucket_count(scope);
+    let capped = activity
+        .user_frequency_constraints
+        .iter()
+        .filter_map(|c| c.max_count.map(|max| over_horizon(c.scope, max)))
+        .min();
+    let requested = activity
+        .user_frequency_constraints
+        .iter()
+        .filter_map(|c| c.min_count.map(|min| over_horizon(c.scope, min)))
+        .chain(
+            activity
+                .frequency_targets
+                .iter()
+                .map(|target| over_horizon(target.scope, target.target_count)),
+        )
+        .max()
+        .unwrap_or(0);
+    (requested, capped)
+}
+
+/// Occurrences worth reserving genes for: an explicit maximum caps the budget; otherwise
+/// the larger of the requested counts and one per day. Never more than fit back to back.
+pub(crate) fn occurrence_budget(problem: &Problem, activity: &Activity) -> usize {
+    let (requested, capped) = occurrence_bounds(problem, activity);
+    let budget = capped.unwrap_or_else(|| requested.max(problem.bucket_count(TimeScope::SameDay)));
+    let fits = problem.total_slots as usize / activity.duration_slots.max(1) as usize;
+    budget.min(fits).min(MAX_OCCURRENCES_PER_ACTIVITY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::candidate_slots::build_candidate_start_slots;
+    use crate::solver::types::{ActivityType, Binding, FrequencyTarget, UserFrequencyConstraint};
+
+    fn base_activity(id: usize) -> Activity {
+        Activity {
+            id,
+            activity_type: ActivityType::Floating,
+            duration_slots: 4,
+            priority: 1.0,
+            assigned_start: None,
+            category_id: 0,
+            input_bindings: Vec::<Binding>::new(),
+            output_bindings: Vec::<Binding>::new(),
+            frequency_targets: Vec::<FrequencyTarget>::new(),
+            user_frequency_constraints: Vec::<UserFrequencyConstraint>::new(),
+            occurrence_spacing: vec![],
+            buffer_before: 0,
+            buffer_after: 0,
+            location_id: None,
+            resource_requirements: vec![],
+            attention_free: false,
+            overlap_compatible_categories: vec![],
+            participant_ids: vec![],
+        }
+    }
+
+    #[test]
+    fn genes_follow_occurrence_budgets_and_score_like_slot_genes() {
+        let mut weekly = base_activity(0);
+        weekly
+            .user_frequency_constraints
+            .push(UserFrequencyConstraint {
+                scope: TimeScope::SameWeek,
+                min_count: Some(1),
+                max_count: Some(2),
+                deadline_end: None,
+                penalty_weight: 100.0,
+            });
+        let daily = base_activity(1);
+
+        let problem = Problem {
+            activities: vec![weekly, daily],
+            floating_indices: vec![0, 1],
+            fixed_indices: vec![],
+            global_constraints: vec![],
+            heatmap: vec![(1, 8, 0.5)],
+            markov_matrix: vec![],
+            total_slots: 280,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let slot_fitness = DiemFitness::new(problem, candidate_slots.clone());
+        let fitness = OccurrenceFitness::new(slot_fitness.clone());
+
+        // Two weekly occurrences (one week), three daily ones (one per touched day).
+        assert_eq!(fitness.owners, vec![0, 0, 1, 1, 1]);
+
+        let unused = fitness.unused_allele;
+        let occurrence_genes = vec![0, unused, 8, unused, unused];
+        let mut slot_genes = vec![slot_fitness.no_activity_allele; candidate_slots.len()];
+        slot_genes[0] = 0;
+        slot_genes[8] = 1;
+
+        assert_eq!(
+            fitness.decode(&occurrence_genes),
+            slot_fitness.decode(&slot_genes)
+        );
+        let occurrence_breakdown = fitness.evaluate(&occurrence_genes);
+        let slot_breakdown = slot_fitness.evaluate(&slot_genes);
+        assert_eq!(
+            occurrence_breakdown.constraint_penalty,
+            slot_breakdown.constraint_penalty
+        );
+        assert_eq!(
+            occurrence_breakdown.habit_reward,
+            slot_breakdown.habit_reward
+        );
+    }
+}
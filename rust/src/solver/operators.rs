@@ -0,0 +1,461 @@
+use super::fitness::DiemFitness;
+use super::types::{TimeScope, TimeSlot};
+use genetic_algorithm::strategy::evolve::prelude::*;
+use rand_08::Rng;
+use std::ops::Range;
+use std::time::Instant;
+
+// Largest shift, in slots, of one occurrence.
+const MAX_SHIFT_SLOTS: i32 = 4;
+
+/// Duration-aware view of slot-indexed genes for the schedule operators.
+#[derive(Debug, Clone)]
+pub struct ScheduleLayout {
+    /// Start slot of each gene, in gene order (sorted).
+    pub gene_starts: Vec<TimeSlot>,
+    /// Duration of each floating choice.
+    pub durations: Vec<u16>,
+    /// `conflicts[a][b]` when floating choices `a` and `b` may not overlap.
+    pub conflicts: Vec<Vec<bool>>,
+    /// Genes of each day, as contiguous ranges.
+    pub day_genes: Vec<Range<usize>>,
+    pub empty_allele: u16,
+    max_duration: u16,
+}
+
+impl ScheduleLayout {
+    pub fn new(fitness: &DiemFitness) -> Self {
+        let problem = &fitness.problem;
+        let floating: Vec<_> = problem
+            .floating_indices
+            .iter()
+            .map(|&act_idx| &problem.activities[act_idx])
+            .collect();
+        let durations: Vec<u16> = floating
+            .iter()
+            .map(|activity| activity.duration_slots)
+            .collect();
+        let conflicts = floating
+            .iter()
+            .map(|a| {
+                floating
+                    .iter()
+                    .map(|b| a.shares_participants(b) && !a.can_overlap(b))
+                    .collect()
+            })
+            .collect();
+
+        let gene_starts = fitness.candidate_start_slots.clone();
+        let mut day_genes = Vec::<Range<usize>>::new();
+        let mut current_day = None;
+        for (gene, &start) in gene_starts.iter().enumerate() {
+            let day = problem.bucket_of(TimeScope::SameDay, start);
+            if current_day == Some(day) {
+                if let Some(range) = day_genes.last_mut() {
+                    range.end = gene + 1;
+                }
+            } else {
+                day_genes.push(gene..gene + 1);
+                current_day = Some(day);
+            }
+        }
+
+        Self {
+            max_duration: durations.iter().copied().max().unwrap_or(0),
+            gene_starts,
+            durations,
+            conflicts,
+            day_genes,
+            empty_allele: fitness.no_activity_allele,
+        }
+    }
+
+    // Empties every other gene whose occurrence would overlap the one placed at `gene`.
+    fn clear_conflicts(&self, genes: &mut [u16], gene: usize) {
+        let choice = genes[gene] as usize;
+        if choice >= self.durations.len() {
+            return;
+        }
+        let start = self.gene_starts[gene] as u32;
+        let end = start + self.durations[choice] as u32;
+        let from = self
+            .gene_starts
+            .partition_point(|&s| s as u32 + (self.max_duration as u32) <= start);
+        let to = self.gene_starts.partition_point(|&s| (s as u32) < end);
+        let empty_allele = self.empty_allele;
+        for (other, allele) in genes.iter_mut().enumerate().take(to).skip(from) {
+            let other_choice = *allele as usize;
+            if other == gene || other_choice >= self.durations.len() {
+                continue;
+            }
+            let other_start = self.gene_starts[other] as u32;
+            let other_end = other_start + self.durations[other_choice] as u32;
+            if other_end > start && self.conflicts[choice][other_choice] {
+                *allele = empty_allele;
+            }
+        }
+    }
+
+    // Empties genes from `boundary` on that conflict with occurrences starting before it
+    // and reaching over it.
+    fn clear_conflicts_across(&self, genes: &mut [u16], boundary: usize) {
+        let Some(&boundary_start) = self.gene_starts.get(boundary) else {
+            return;
+        };
+        let from = self
+            .gene_starts
+            .partition_point(|&s| s as u32 + (self.max_duration as u32) <= boundary_start as u32);
+        for gene in from..boundary {
+            self.clear_conflicts(genes, gene);
+        }
+    }
+
+    // An empty gene starting at `slot`, if there is one.
+    fn gene_at(&self, genes: &[u16], slot: TimeSlot) -> Option<usize> {
+        let from = self.gene_starts.partition_point(|&s| s < slot);
+        let to = self.gene_starts.partition_point(|&s| s <= slot);
+        (from..to).find(|&gene| genes[gene] == self.empty_allele)
+    }
+}
+
+/// Mutation for slot-indexed genes that keeps occurrences apart: each mutation places a
+/// random choice at a random gene, shifts an occurrence by up to `MAX_SHIFT_SLOTS`, or
+/// swaps the activities of two occurrences, then clears genes the moved occurrences cover.
+#[derive(Debug, Clone)]
+pub struct MutateSchedule {
+    pub layout: ScheduleLayout,
+    pub number_of_mutations: usize,
+    pub mutation_probability: f32,
+}
+
+impl MutateSchedule {
+    pub fn new(
+        layout: ScheduleLayout,
+        number_of_mutations: usize,
+        mutation_probability: f32,
+    ) -> Self {
+        Self {
+            layout,
+            number_of_mutations,
+            mutation_probability,
+        }
+    }
+
+    fn place<R: Rng>(&self, genes: &mut [u16], rng: &mut R) {
+        let gene = rng.gen_range(0..genes.len());
+        genes[gene] = rng.gen_range(0..=self.layout.empty_allele);
+        self.layout.clear_conflicts(genes, gene);
+    }
+
+    fn shift<R: Rng>(&self, genes: &mut [u16], occupied: &[usize], rng: &mut R) {
+        let gene = occupied[rng.gen_range(0..occupied.len())];
+        let offset = rng.gen_range(1..=MAX_SHIFT_SLOTS) * if rng.gen_bool(0.5) { 1 } else { -1 };
+        let target = self.layout.gene_starts[gene] as i32 + offset;
+        let Some(target_gene) = u16::try_from(target)
+            .ok()
+            .and_then(|slot| self.layout.gene_at(genes, slot))
+        else {
+            return;
+        };
+        genes[target_gene] = genes[gene];
+        genes[gene] = self.layout.empty_allele;
+        self.layout.clear_conflicts(genes, target_gene);
+    }
+
+    // One to `number_of_mutations` random place, shift or swap mutations.
+    fn mutate_genes<R: Rng>(&self, genes: &mut [u16], rng: &mut R) {
+        for _ in 0..rng.gen_range(1..=self.number_of_mutations.max(1)) {
+            let occupied: Vec<usize> = (0..genes.len())
+                .filter(|&gene| genes[gene] != self.layout.empty_allele)
+                .collect();
+            match rng.gen_range(0..3) {
+                1 if !occupied.is_empty() => self.shift(genes, &occupied, rng),
+                2 if occupied.len() > 1 => self.swap(genes, &occupied, rng),
+                _ => self.place(genes, rng),
+            }
+        }
+    }
+
+    fn swap<R: Rng>(&self, genes: &mut [u16], occupied: &[usize], rng: &mut R) {
+        let a = occupied[rng.gen_range(0..occupied.len())];
+        let b = occupied[rng.gen_range(0..occupied.len())];
+        if genes[a] == genes[b] {
+            return;
+        }
+        genes.swap(a, b);
+        self.layout.clear_conflicts(genes, a);
+        if genes[b] != self.layout.empty_allele {
+            self.layout.clear_conflicts(genes, b);
+        }
+    }
+}
+
+impl Mutate for MutateSchedule {
+    type Genotype = RangeGenotype<u16>;
+
+    fn call<R: Rng, SR: StrategyReporter<Genotype = Self::Genotype>>(
+        &mut self,
+        genotype: &Self::Genotype,
+        state: &mut EvolveState<Self::Genotype>,
+        _config: &EvolveConfig,
+        _reporter: &mut SR,
+        rng: &mut R,
+    ) {
+        let now = Instant::now();
+        for chromosome in state
+            .population
+            .chromosomes
+            .iter_mut()
+            .filter(|c| c.is_offspring())
+        {
+            if chromosome.genes.is_empty() || !rng.gen_bool(self.mutation_probability as f64) {
+                continue;
+            }
+            self.mutate_genes(&mut chromosome.genes, rng);
+            chromosome.reset_metadata(genotype.genes_hashing);
+        }
+        state.add_duration(StrategyAction::Mutate, now.elapsed());
+    }
+}
+
+/// Crossover that swaps whole days between two parents, each day with even odds, so
+/// children inherit days that already fit together. Where neighbouring days come from
+/// different parents, occurrences running over midnight clear what they now overlap.
+#[derive(Debug, Clone)]
+pub struct CrossoverDayBlock {
+    pub layout: ScheduleLayout,
+    pub selection_rate: f32,
+    pub crossover_rate: f32,
+}
+
+impl CrossoverDayBlock {
+    pub fn new(layout: ScheduleLayout, selection_rate: f32, crossover_rate: f32) -> Self {
+        Self {
+            layout,
+            selection_rate,
+            crossover_rate,
+        }
+    }
+
+    fn cross<R: Rng>(&self, father: &mut [u16], mother: &mut [u16], rng: &mut R) {
+        let swapped: Vec<bool> = self
+            .layout
+            .day_genes
+            .iter()
+            .map(|days| {
+                let swap = rng.gen_bool(0.5);
+                if swap {
+                    father[days.clone()].swap_with_slice(&mut mother[days.clone()]);
+                }
+                swap
+            })
+            .collect();
+        for (day, days) in self.layout.day_genes.iter().enumerate().skip(1) {
+            if swapped[day] != swapped[day - 1] {
+                self.layout.clear_conflicts_across(father, days.start);
+                self.layout.clear_conflicts_across(mother, days.start);
+            }
+        }
+    }
+}
+
+impl Crossover for CrossoverDayBlock {
+    type Genotype = RangeGenotype<u16>;
+
+    fn call<R: Rng, SR: StrategyReporter<Genotype = Self::Genotype>>(
+        &mut self,
+        genotype: &Self::Genotype,
+        state: &mut EvolveState<Self::Genotype>,
+        _config: &EvolveConfig,
+        _reporter: &mut SR,
+        rng: &mut R,
+    ) {
+        let now = Instant::now();
+        let existing_population_size = state.population.chromosomes.len();
+        let selected_population_size =
+            (existing_population_size as f32 * self.selection_rate).ceil() as usize;
+        state.population.increment_age();
+        state
+            .population
+            .extend_from_within(selected_population_size);
+
+        let offspring = &mut state.population.chromosomes[existing_population_size..];
+        for pair in offspring.chunks_mut(2) {
+            let [father, mother] = pair else {
+                pair[0].reset_age();
+                continue;
+            };
+            if rng.gen_bool(self.crossover_rate as f64) {
+                self.cross(&mut father.genes, &mut mother.genes, rng);
+                father.reset_metadata(genotype.genes_hashing);
+                mother.reset_metadata(genotype.genes_hashing);
+            } else {
+                father.reset_age();
+                mother.reset_age();
+            }
+        }
+        state.add_duration(StrategyAction::Crossover, now.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::candidate_slots::build_candidate_start_slots;
+    use crate::solver::types::{
+        Activity, ActivityType, Binding, FrequencyTarget, Problem, UserFrequencyConstraint,
+    };
+    use rand_08::rngs::StdRng;
+    use rand_08::SeedableRng;
+
+    fn base_activity(id: usize, duration_slots: u16) -> Activity {
+        Activity {
+            id,
+            activity_type: ActivityType::Floating,
+            duration_slots,
+            priority: 1.0,
+            assigned_start: None,
+            category_id: 0,
+            input_bindings: Vec::<Binding>::new(),
+            output_bindings: Vec::<Binding>::new(),
+            frequency_targets: Vec::<FrequencyTarget>::new(),
+            user_frequency_constraints: Vec::<UserFrequencyConstraint>::new(),
+            occurrence_spacing: vec![],
+            buffer_before: 0,
+            buffer_after: 0,
+            location_id: None,
+            resource_requirements: vec![],
+            attention_free: false,
+            overlap_compatible_categories: vec![],
+            participant_ids: vec![],
+        }
+    }
+
+    // A 4-slot and a 2-slot activity that conflict, and a podcast that overlaps anything,
+    // over one full day and the start of the next.
+    fn two_day_layout() -> ScheduleLayout {
+        let mut podcast = base_activity(2, 2);
+        podcast.attention_free = true;
+        let problem = Problem {
+            activities: vec![base_activity(0, 4), base_activity(1, 2), podcast],
+            floating_indices: vec![0, 1, 2],
+            fixed_indices: vec![],
+            global_constraints: vec![],
+            heatmap: vec![],
+            markov_matrix: vec![],
+            total_slots: 110,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+        let candidate_slots = build_candidate_start_slots(&problem);
+        let fitness = DiemFitness::new(problem, candidate_slots);
+        ScheduleLayout::new(&fitness)
+    }
+
+    // Pairs of occupied genes whose occurrences overlap although they conflict.
+    fn conflicting_pairs(layout: &ScheduleLayout, genes: &[u16]) -> Vec<(usize, usize)> {
+        let occupied: Vec<usize> = (0..genes.len())
+            .filter(|&gene| genes[gene] != layout.empty_allele)
+            .collect();
+        let mut pairs = vec![];
+        for (i, &a) in occupied.iter().enumerate() {
+            for &b in &occupied[i + 1..] {
+                let (choice_a, choice_b) = (genes[a] as usize, genes[b] as usize);
+                let a_end = layout.gene_starts[a] + layout.durations[choice_a];
+                if a_end > layout.gene_starts[b] && layout.conflicts[choice_a][choice_b] {
+                    pairs.push((a, b));
+                }
+            }
+        }
+        pairs
+    }
+
+    #[test]
+    fn placing_an_occurrence_clears_the_genes_it_overlaps() {
+        let layout = two_day_layout();
+        assert_eq!(layout.day_genes, vec![0..96, 96..110]);
+
+        let empty = layout.empty_allele;
+        let mut genes = vec![empty; 110];
+        genes[8] = 0; // runs into slot 10
+        genes[12] = 1;
+        genes[13] = 2; // may overlap anything
+        genes[14] = 1; // starts right after
+        genes[10] = 0; // occupies 10..14
+
+        layout.clear_conflicts(&mut genes, 10);
+
+        assert_eq!(genes[8], empty);
+        assert_eq!(genes[12], empty);
+        assert_eq!(genes[13], 2);
+        assert_eq!(genes[14], 1);
+        assert_eq!(genes[10], 0);
+    }
+
+    #[test]
+    fn shift_only_moves_into_empty_genes() {
+        let layout = two_day_layout();
+        let mutate = MutateSchedule::new(layout.clone(), 1, 1.0);
+        let empty = layout.empty_allele;
+        // Podcasts fill every slot a shift of the occurrence at 10 could reach.
+        let mut genes = vec![empty; 110];
+        genes[6..=14].fill(2);
+        genes[10] = 0;
+        assert_eq!(layout.gene_at(&genes, 12), None);
+
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..50 {
+            let before = genes.clone();
+            mutate.shift(&mut genes, &[10], &mut rng);
+            assert_eq!(genes, before);
+        }
+
+        genes[12] = empty;
+        assert_eq!(layout.gene_at(&genes, 12), Some(12));
+    }
+
+    #[test]
+    fn mutations_keep_the_schedule_free_of_conflicts() {
+        let layout = two_day_layout();
+        let mutate = MutateSchedule::new(layout.clone(), 3, 1.0);
+        let mut genes = vec![layout.empty_allele; 110];
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..2_000 {
+            mutate.mutate_genes(&mut genes, &mut rng);
+            assert_eq!(conflicting_pairs(&layout, &genes), vec![], "{:?}", genes);
+        }
+        assert!(genes.iter().any(|&allele| allele != layout.empty_allele));
+    }
+
+    #[test]
+    fn day_block_crossover_clears_conflicts_over_midnight() {
+        let layout = two_day_layout();
+        let crossover = CrossoverDayBlock::new(layout.clone(), 0.5, 1.0);
+        let empty = layout.empty_allele;
+        // The father's late occurrence runs to slot 98; the mother starts one at 96.
+        let mut father = vec![empty; 110];
+        father[94] = 0;
+        let mut mother = vec![empty; 110];
+        mother[96] = 1;
+
+        let mut mixed = 0;
+        for seed in 0..32 {
+            let (mut son, mut daughter) = (father.clone(), mother.clone());
+            crossover.cross(&mut son, &mut daughter, &mut StdRng::seed_from_u64(seed));
+            assert_eq!(conflicting_pairs(&layout, &son), vec![]);
+            assert_eq!(conflicting_pairs(&layout, &daughter), vec![]);
+            // One child got the father's first day and the mother's second.
+            if son[94] == 0 && daughter[96] == empty || daughter[94] == 0 && son[96] == empty {
+                mixed += 1;
+            }
+        }
+        assert!(
+            mixed > 0,
+            "no seed combined the father's day with the mother's next day"
+        );
+    }
+}
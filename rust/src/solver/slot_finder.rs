@@ -0,0 +1,206 @@
+use super::candidate_slots::build_candidate_start_slots;
+use super::fitness::DiemFitness;
+use super::types::{ActivityId, Problem, TimeSlot};
+use genetic_algorithm::chromosome::Chromosome;
+use genetic_algorithm::fitness::Fitness;
+use genetic_algorithm::genotype::{Genotype, RangeGenotype};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlotSuggestion {
+    pub start: TimeSlot,
+    /// Rank key of the schedule with this occurrence added: the packed lexicographic fitness
+    /// relative to the fixed schedule alone. Its tiers are packed at different scales, so it
+    /// only orders suggestions; higher is better.
+    pub score: isize,
+}
+
+/// Ranks feasible start slots for one more occurrence of `activity_id` with the given
+/// duration, on top of the problem's fixed events, without running the GA.
+///
+/// Slots that would overlap a conflicting fixed event or its buffers, touch a forbidden
+/// zone or run past the horizon are skipped. Returns at most `limit` suggestions, best first.
+pub fn find_slots(
+    problem: &Problem,
+    activity_id: ActivityId,
+    duration_slots: u16,
+    limit: usize,
+) -> Result<Vec<SlotSuggestion>, Box<dyn Error>> {
+    let act_idx = problem
+        .activities
+        .iter()
+        .position(|activity| activity.id == activity_id)
+        .ok_or_else(|| format!("Unknown activity id: {}", activity_id))?;
+    if duration_slots == 0 || limit == 0 {
+        return Ok(vec![]);
+    }
+
+    // Only the queried activity floats; everything else stays as already planned.
+    let mut query = problem.clone();
+    query.activities[act_idx].duration_slots = duration_slots;
+    query.floating_indices = vec![act_idx];
+    query.fixed_indices.retain(|&idx| idx != act_idx);
+
+    let mut candidate_start_slots = build_candidate_start_slots(&query);
+    candidate_start_slots.dedup();
+
+    let genotype = RangeGenotype::builder()
+        .with_genes_size(1)
+        .with_allele_range(0..=1)
+        .build()
+        .map_err(|e| format!("Genotype build error: {:?}", e))?;
+    let mut fitness = DiemFitness::new(query, vec![0]);
+    let baseline = fitness
+        .calculate_for_chromosome(
+            &Chromosome::new(vec![fitness.no_activity_allele]),
+            &genotype,
+        )
+        .unwrap_or(0);
+
+    let mut suggestions = Vec::new();
+    for start in candidate_start_slots {
+        if !is_feasible(&fitness.problem, act_idx, start) {
+            continue;
+        }
+        fitness.candidate_start_slots = vec![start];
+        if let Some(value) = fitness.calculate_for_chromosome(&Chromosome::new(vec![0]), &genotype)
+        {
+            suggestions.push(SlotSuggestion {
+                start,
+                score: value - baseline,
+            });
+        }
+    }
+
+    suggestions.sort_by(|a, b| b.score.cmp(&a.score).then(a.start.cmp(&b.start)));
+    suggestions.truncate(limit);
+    Ok(suggestions)
+}
+
+fn is_feasible(problem: &Problem, act_idx: usize, start: TimeSlot) -> bool {
+    let activity = &problem.activities[act_idx];
+    let end = start as u32 + activity.duration_slots as u32;
+    if end > problem.total_slots as u32 {
+        return false;
+    }
+
+    if problem.in_forbidden_zone(activity.id, start, activity.duration_slots) {
+        return false;
+    }
+
+    !problem.fixed_indices.iter().any(|&idx| {
+        let fixed = &problem.activities[idx];
+        fixed.assigned_start.is_some_and(|fixed_start| {
+            fixed.shares_participants(activity)
+                && !fixed.can_overlap(activity)
+                && !fixed.clear_with_buffers(fixed_start, activity, start)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::types::{
+        Activity, ActivityType, Binding, FrequencyTarget, GlobalConstraint, UserFrequencyConstraint,
+    };
+
+    fn base_activity(id: usize, kind: ActivityType) -> Activity {
+        Activity {
+            id,
+            activity_type: kind,
+            duration_slots: 2,
+            priority: 1.0,
+            assigned_start: None,
+            category_id: 0,
+            input_bindings: Vec::<Binding>::new(),
+            output_bindings: Vec::<Binding>::new(),
+            frequency_targets: Vec::<FrequencyTarget>::new(),
+            user_frequency_constraints: Vec::<UserFrequencyConstraint>::new(),
+            occurrence_spacing: vec![],
+            buffer_before: 0,
+            buffer_after: 0,
+            location_id: None,
+            resource_requirements: vec![],
+            attention_free: false,
+            overlap_compatible_categories: vec![],
+            participant_ids: vec![],
+        }
+    }
+
+    #[test]
+    fn suggestions_skip_conflicts_and_follow_heatmap() {
+        let mut meeting = base_activity(0, ActivityType::Fixed);
+        meeting.assigned_start = Some(4);
+        meeting.duration_slots = 4;
+        let gym = base_activity(1, ActivityType::Floating);
+
+        let problem = Problem {
+            activities: vec![meeting, gym],
+            floating_indices: vec![1],
+            fixed_indices: vec![0],
+            global_constraints: vec![GlobalConstraint::ForbiddenZone {
+                start: 12,
+                end: 14,
+                activity_id: Some(1),
+            }],
+            heatmap: vec![(1, 10, 0.9), (1, 3, 0.9), (1, 0, 0.2)],
+            markov_matrix: vec![],
+            total_slots: 16,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let suggestions = find_slots(&problem, 1, 2, 3).expect("query should succeed");
+        let starts: Vec<TimeSlot> = suggestions.iter().map(|s| s.start).collect();
+
+        // Slot 3 would run into the meeting; 11-13 touch the forbidden zone; 15 overruns.
+        assert_eq!(starts, vec![10, 0, 1]);
+        assert!(suggestions[0].score > suggestions[1].score);
+    }
+
+    #[test]
+    fn suggestions_keep_clear_of_fixed_buffers() {
+        let mut meeting = base_activity(0, ActivityType::Fixed);
+        meeting.assigned_start = Some(6);
+        meeting.duration_slots = 2;
+        meeting.buffer_before = 1;
+        meeting.buffer_after = 2;
+        let mut gym = base_activity(1, ActivityType::Floating);
+        gym.buffer_before = 1;
+
+        let problem = Problem {
+            activities: vec![meeting, gym],
+            floating_indices: vec![1],
+            fixed_indices: vec![0],
+            global_constraints: vec![],
+            heatmap: vec![
+                (1, 3, 0.9),
+                (1, 4, 0.9),
+                (1, 9, 0.9),
+                (1, 10, 0.9),
+                (1, 11, 0.5),
+            ],
+            markov_matrix: vec![],
+            total_slots: 14,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+
+        let suggestions = find_slots(&problem, 1, 2, 2).expect("query should succeed");
+        let starts: Vec<TimeSlot> = suggestions.iter().map(|s| s.start).collect();
+
+        // 4 and 9-10 sit in the meeting's buffers; 3 leaves one slot before it, and 11
+        // leaves the meeting's two after plus the gym's one before.
+        assert_eq!(starts, vec![3, 11]);
+    }
+}
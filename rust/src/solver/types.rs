@@ -1,5 +1,4 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
 /// Represents a 15-minute time slot.
 pub type TimeSlot = u16;
@@ -8,6 +7,46 @@ pub type TimeSlot = u16;
 /// The caller must map external UUIDs to these sequential integers (0..N).
 pub type ActivityId = usize;
 pub type CategoryId = usize;
+pub type LocationId = usize;
+pub type ResourceId = usize;
+pub type ParticipantId = usize;
+
+/// Heatmap probabilities as one dense row of slots per activity that has entries.
+#[derive(Debug, Clone, Default)]
+pub struct HeatmapTable {
+    // Offset of each activity's row in `values`, if it has one.
+    rows: Vec<Option<usize>>,
+    width: usize,
+    values: Vec<f32>,
+}
+
+impl HeatmapTable {
+    /// Probability of `activity_id` starting at `slot`, or 0.0 without an entry.
+    pub fn get(&self, activity_id: ActivityId, slot: TimeSlot) -> f32 {
+        match self.rows.get(activity_id) {
+            Some(Some(row)) if (slot as usize) < self.width => self.values[row + slot as usize],
+            _ => 0.0,
+        }
+    }
+}
+
+/// Markov transition probabilities as a dense square matrix over activity ids.
+#[derive(Debug, Clone, Default)]
+pub struct MarkovTable {
+    size: usize,
+    values: Vec<f32>,
+}
+
+impl MarkovTable {
+    /// Probability of `to` following `from`, or 0.0 without an entry.
+    pub fn get(&self, from: ActivityId, to: ActivityId) -> f32 {
+        if from < self.size && to < self.size {
+            self.values[from * self.size + to]
+        } else {
+            0.0
+        }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ActivityType {
@@ -22,6 +61,17 @@ pub enum TimeScope {
     SameMonth,
 }
 
+impl TimeScope {
+    /// Calendar bucket length in slots; `SameMonth` spans the whole horizon.
+    pub fn bucket_slots(&self) -> u32 {
+        match self {
+            TimeScope::SameDay => 96,
+            TimeScope::SameWeek => 672,
+            TimeScope::SameMonth => u32::MAX,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FrequencyTarget {
     pub scope: TimeScope,
@@ -39,6 +89,36 @@ pub struct UserFrequencyConstraint {
     pub penalty_weight: f32,
 }
 
+/// Allowed gap between the starts of two consecutive occurrences of one activity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OccurrenceSpacing {
+    pub min_gap: u16,
+    #[serde(default)]
+    pub max_gap: Option<u16>,
+    pub weight: f32,
+}
+
+/// Shared household resource (car, home office, equipment) with a concurrent capacity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Resource {
+    pub id: ResourceId,
+    pub name: String,
+    pub capacity: u16,
+}
+
+/// Household member whose time is scheduled jointly with the others.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Participant {
+    pub id: ParticipantId,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceRequirement {
+    pub resource_id: ResourceId,
+    pub amount: u16,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Binding {
     /// Disjunctive Normal Form (OR of ANDs)
@@ -51,6 +131,31 @@ pub struct Binding {
     pub weight: f32,
 }
 
+/// Selects occurrences either by activity id or by category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ActivitySelector {
+    Activity(ActivityId),
+    Category(CategoryId),
+}
+
+impl ActivitySelector {
+    pub fn matches(&self, activity: &Activity) -> bool {
+        match self {
+            ActivitySelector::Activity(id) => activity.id == *id,
+            ActivitySelector::Category(id) => activity.category_id == *id,
+        }
+    }
+}
+
+/// Window in which two mutually exclusive occurrences must not co-occur.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum ExclusionWindow {
+    /// Both occurrences fall into the same day/week/month bucket, in either order.
+    Scope(TimeScope),
+    /// `second` starts between `min_lag` and `max_lag` slots after `first` ends.
+    Lag { min_lag: u16, max_lag: u16 },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Activity {
     pub id: ActivityId,
@@ -69,6 +174,79 @@ pub struct Activity {
     pub frequency_targets: Vec<FrequencyTarget>,
     #[serde(default)]
     pub user_frequency_constraints: Vec<UserFrequencyConstraint>,
+    /// Entry `i` governs the gap after the `i`-th occurrence; the last entry repeats.
+    /// A single entry spaces all repeats evenly, `[1d, 3d, 7d]` models spaced repetition.
+    #[serde(default)]
+    pub occurrence_spacing: Vec<OccurrenceSpacing>,
+    /// Free slots required right before/after this activity (e.g. prep before meetings).
+    #[serde(default)]
+    pub buffer_before: u16,
+    #[serde(default)]
+    pub buffer_after: u16,
+    /// Where the activity takes place; `None` for location-independent activities.
+    #[serde(default)]
+    pub location_id: Option<LocationId>,
+    /// Resources held for the whole duration of each occurrence.
+    #[serde(default)]
+    pub resource_requirements: Vec<ResourceRequirement>,
+    /// Background activity (laundry, podcast) that may run alongside anything else.
+    #[serde(default)]
+    pub attention_free: bool,
+    /// Categories this activity may run in parallel with.
+    #[serde(default)]
+    pub overlap_compatible_categories: Vec<CategoryId>,
+    /// Participants who must all attend; empty means every participant (or the single
+    /// user of a one-person problem).
+    #[serde(default)]
+    pub participant_ids: Vec<ParticipantId>,
+}
+
+impl Activity {
+    /// Whether occurrences of the two activities may overlap without a conflict.
+    pub fn can_overlap(&self, other: &Activity) -> bool {
+        self.attention_free
+            || other.attention_free
+            || self
+                .overlap_compatible_categories
+                .contains(&other.category_id)
+            || other
+                .overlap_compatible_categories
+                .contains(&self.category_id)
+    }
+
+    /// Whether the two activities compete for at least one participant's time.
+    pub fn shares_participants(&self, other: &Activity) -> bool {
+        self.participant_ids.is_empty()
+            || other.participant_ids.is_empty()
+            || self
+                .participant_ids
+                .iter()
+                .any(|id| other.participant_ids.contains(id))
+    }
+
+    /// Whether an occurrence at `start` and one of `other` at `other_start` are apart by at
+    /// least the buffers facing each other.
+    pub fn clear_with_buffers(
+        &self,
+        start: TimeSlot,
+        other: &Activity,
+        other_start: TimeSlot,
+    ) -> bool {
+        let end = start as u32 + self.duration_slots as u32;
+        let other_end = other_start as u32 + other.duration_slots as u32;
+        if end <= other_start as u32 {
+            other_start as u32 - end >= self.buffer_after as u32 + other.buffer_before as u32
+        } else if other_end <= start as u32 {
+            start as u32 - other_end >= other.buffer_after as u32 + self.buffer_before as u32
+        } else {
+            false
+        }
+    }
+
+    /// Whether participant `id` attends this activity.
+    pub fn involves(&self, id: ParticipantId) -> bool {
+        self.participant_ids.is_empty() || self.participant_ids.contains(&id)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -90,6 +268,53 @@ pub enum GlobalConstraint {
         #[serde(default)]
         deadline_end: Option<TimeSlot>,
     },
+    /// Explicit ordering edge: every occurrence of `successor_id` must start between
+    /// `min_lag` and `max_lag` slots after some occurrence of `predecessor_id` ends.
+    Precedence {
+        predecessor_id: ActivityId,
+        successor_id: ActivityId,
+        #[serde(default)]
+        min_lag: u16,
+        #[serde(default)]
+        max_lag: Option<u16>,
+        weight: f32,
+    },
+    /// Caps busy slots per calendar day/week (or the whole horizon for `SameMonth`),
+    /// overall or for one category. Hard limits use the hard-constraint penalty per
+    /// excess slot; soft limits use `penalty_weight`.
+    WorkloadLimit {
+        scope: TimeScope,
+        #[serde(default)]
+        category_id: Option<CategoryId>,
+        max_busy_slots: u16,
+        #[serde(default)]
+        hard: bool,
+        #[serde(default)]
+        penalty_weight: f32,
+    },
+    /// Keeps at least `min_free_slots` unscheduled per bucket, counting only slots that
+    /// are not globally forbidden. Penalized by `weight` per missing slot.
+    FreeTimeTarget {
+        scope: TimeScope,
+        min_free_slots: u16,
+        weight: f32,
+    },
+    /// After at most `max_busy_slots` of continuous activity, require a gap of at least
+    /// `min_break_slots`. Shorter gaps do not end the busy streak.
+    ContinuousBusyLimit {
+        #[serde(default)]
+        category_id: Option<CategoryId>,
+        max_busy_slots: u16,
+        min_break_slots: u16,
+        weight: f32,
+    },
+    /// Negative binding: penalizes every pair of `first`/`second` occurrences inside `window`.
+    Exclusion {
+        first: ActivitySelector,
+        second: ActivitySelector,
+        window: ExclusionWindow,
+        weight: f32,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -104,28 +329,150 @@ pub struct Problem {
     pub heatmap: Vec<(ActivityId, TimeSlot, f32)>,
     // (FromActivityId, ToActivityId, Probability)
     pub markov_matrix: Vec<(ActivityId, ActivityId, f32)>,
+    // (FromCategoryId, ToCategoryId, Cost) charged when adjacent activities switch category
+    #[serde(default)]
+    pub category_switch_costs: Vec<(CategoryId, CategoryId, f32)>,
+    // (CategoryId, Bonus) granted when adjacent activities stay in the same category
+    #[serde(default)]
+    pub category_batching_bonus: Vec<(CategoryId, f32)>,
+    // (FromLocationId, ToLocationId, TravelSlots); a missing reverse entry mirrors the forward one
+    #[serde(default)]
+    pub travel_times: Vec<(LocationId, LocationId, u16)>,
+    #[serde(default)]
+    pub resources: Vec<Resource>,
+    /// Household members for joint scheduling; empty for a single-person problem.
+    #[serde(default)]
+    pub participants: Vec<Participant>,
 
     pub total_slots: u16,
+    /// Slot-of-day at which horizon slot 0 falls (e.g. 49 for a 12:15 start), so that
+    /// day/week buckets follow real midnights. Day 0 is treated as a Monday.
+    #[serde(default)]
+    pub day_start_offset: TimeSlot,
 }
 
 impl Problem {
-    // Helper to build fast lookup maps (transient)
-    pub fn build_lookup_maps(
+    /// Calendar bucket of `slot` for `scope`; the partial first day/week is bucket 0.
+    pub fn bucket_of(&self, scope: TimeScope, slot: TimeSlot) -> usize {
+        ((slot as u32 + self.day_start_offset as u32) / scope.bucket_slots()) as usize
+    }
+
+    /// Number of buckets touched by the horizon, including a partial last bucket.
+    pub fn bucket_count(&self, scope: TimeScope) -> usize {
+        self.bucket_of(scope, self.total_slots) + 1
+    }
+
+    /// First horizon slot belonging to `bucket`.
+    pub fn bucket_start(&self, scope: TimeScope, bucket: usize) -> TimeSlot {
+        let start = (bucket as u32)
+            .saturating_mul(scope.bucket_slots())
+            .saturating_sub(self.day_start_offset as u32);
+        start.min(self.total_slots as u32) as TimeSlot
+    }
+
+    /// Whether an occurrence of `activity_id` over `start..start + duration` touches a
+    /// forbidden zone that applies to it.
+    pub fn in_forbidden_zone(
         &self,
-    ) -> (
-        HashMap<(ActivityId, TimeSlot), f32>,
-        HashMap<(ActivityId, ActivityId), f32>,
-    ) {
-        let mut h_map = HashMap::new();
-        for (a, t, p) in &self.heatmap {
-            h_map.insert((*a, *t), *p);
+        activity_id: ActivityId,
+        start: TimeSlot,
+        duration: u16,
+    ) -> bool {
+        let end = start as u32 + duration as u32;
+        self.global_constraints.iter().any(|constraint| {
+            matches!(
+                constraint,
+                GlobalConstraint::ForbiddenZone { start: zone_start, end: zone_end, activity_id: zone_activity }
+                    if (start as u32) < *zone_end as u32
+                        && end > *zone_start as u32
+                        && zone_activity.is_none_or(|id| id == activity_id)
+            )
+        })
+    }
+
+    /// Dense heatmap and Markov tables for the hot scoring loop, sized by the activities
+    /// and the horizon. Entries naming an unknown activity or a slot past the horizon are
+    /// skipped; later duplicate entries win, as they did in the serialized vectors.
+    pub fn build_lookup_tables(&self) -> (HeatmapTable, MarkovTable) {
+        let size = self.activities.len();
+        let mut heatmap = HeatmapTable {
+            rows: vec![None; size],
+            width: self.total_slots as usize,
+            values: vec![],
+        };
+        for &(activity_id, slot, probability) in &self.heatmap {
+            if activity_id >= size || slot as usize >= heatmap.width {
+                continue;
+            }
+            let row = *heatmap.rows[activity_id].get_or_insert_with(|| {
+                heatmap
+                    .values
+                    .resize(heatmap.values.len() + heatmap.width, 0.0);
+                heatmap.values.len() - heatmap.width
+            });
+            heatmap.values[row + slot as usize] = probability;
         }
 
-        let mut m_map = HashMap::new();
-        for (f, t, p) in &self.markov_matrix {
-            m_map.insert((*f, *t), *p);
+        let mut markov = MarkovTable {
+            size,
+            values: vec![0.0; size * size],
+        };
+        for &(from, to, probability) in &self.markov_matrix {
+            if from < size && to < size {
+                markov.values[from * size + to] = probability;
+            }
         }
 
-        (h_map, m_map)
+        (heatmap, markov)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_tables_skip_entries_outside_the_problem() {
+        let activity = Activity {
+            id: 0,
+            activity_type: ActivityType::Floating,
+            duration_slots: 2,
+            priority: 1.0,
+            assigned_start: None,
+            category_id: 0,
+            input_bindings: vec![],
+            output_bindings: vec![],
+            frequency_targets: vec![],
+            user_frequency_constraints: vec![],
+            occurrence_spacing: vec![],
+            buffer_before: 0,
+            buffer_after: 0,
+            location_id: None,
+            resource_requirements: vec![],
+            attention_free: false,
+            overlap_compatible_categories: vec![],
+            participant_ids: vec![],
+        };
+        let problem = Problem {
+            activities: vec![activity],
+            floating_indices: vec![0],
+            fixed_indices: vec![],
+            global_constraints: vec![],
+            heatmap: vec![(0, 3, 0.5), (usize::MAX, 3, 0.9), (0, 10, 0.9)],
+            markov_matrix: vec![(0, 0, 0.4), (0, usize::MAX, 0.9), (usize::MAX, 0, 0.9)],
+            total_slots: 10,
+            day_start_offset: 0,
+            category_switch_costs: vec![],
+            category_batching_bonus: vec![],
+            travel_times: vec![],
+            resources: vec![],
+            participants: vec![],
+        };
+        let (heatmap, markov) = problem.build_lookup_tables();
+        assert_eq!(heatmap.get(0, 3), 0.5);
+        assert_eq!(heatmap.get(0, 10), 0.0);
+        assert_eq!(heatmap.values.len(), 10);
+        assert_eq!(markov.get(0, 0), 0.4);
+        assert_eq!(markov.values.len(), 1);
     }
 }